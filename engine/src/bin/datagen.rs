@@ -0,0 +1,251 @@
+//! Generate training data from fixed node self-play games
+//!
+//! Usage: `datagen <output> [--games N] [--nodes N] [--processes N] [--seed N]`
+//!
+//! Each worker process plays its share of the games from openings made of random moves, seeded
+//! from the base seed and its index so that runs are reproducible. Quiet positions are written to
+//! `<output>-<worker>.bin` as packed records, and to `<output>-<worker>.txt` as `fen;score;result`
+//! lines, with scores from White's point of view and results 1.0, 0.5 or 0.0 for White.
+//!
+//! A packed record is 32 bytes: the occupancy bitboard, one nibble per occupied square in order of
+//! the occupancy holding the colour in its top bit and the piece type below it, the side to move,
+//! the score as a little endian i16, the result as 0, 1 or 2 for a loss, draw or win for White,
+//! and four bytes of padding.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::{Command, exit};
+
+use chust_engine::{Board, Engine, WHITE};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use selfplay::{insufficient, random_opening};
+
+mod selfplay;
+
+/// Number of random plies played before the engine takes over
+const OPENING_PLIES: usize = 8;
+/// Positions in the opening are too close to the random moves to be useful
+const SKIP_PLIES: usize = 16;
+/// Games still going after this many plies are drawn
+const MAX_PLIES: usize = 400;
+/// Score beyond which a game is adjudicated once it has held for `ADJUDICATION_PLIES`
+const ADJUDICATION_SCORE: i32 = 2_000;
+const ADJUDICATION_PLIES: usize = 6;
+/// Positions with scores beyond this are decided and would only add noise
+const MAX_RECORDED_SCORE: i32 = 3_000;
+
+struct Config {
+    output: String,
+    games: usize,
+    nodes: usize,
+    processes: usize,
+    seed: u64,
+    /// Index of this process when it is a worker spawned by the parent process
+    worker: Option<usize>,
+}
+
+struct Sample {
+    board: Board,
+    /// Search score from White's point of view
+    score: i32,
+}
+
+fn main() {
+    let config = parse_args().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        eprintln!("usage: datagen <output> [--games N] [--nodes N] [--processes N] [--seed N]");
+        exit(1);
+    });
+
+    if let Some(worker) = config.worker {
+        if let Err(e) = generate(&config, worker) {
+            eprintln!("worker {worker}: {e}");
+            exit(1);
+        }
+        return;
+    }
+
+    let exe = std::env::current_exe().expect("should be able to find the datagen executable");
+    let children: Vec<_> = (0..config.processes)
+        .map(|worker| {
+            Command::new(&exe)
+                .args([
+                    &config.output,
+                    "--games",
+                    &config.games.to_string(),
+                    "--nodes",
+                    &config.nodes.to_string(),
+                    "--processes",
+                    &config.processes.to_string(),
+                    "--seed",
+                    &config.seed.to_string(),
+                    "--worker",
+                    &worker.to_string(),
+                ])
+                .spawn()
+                .expect("should be able to spawn worker processes")
+        })
+        .collect();
+
+    let mut failed = false;
+    for mut child in children {
+        failed |= !child.wait().is_ok_and(|status| status.success());
+    }
+    if failed {
+        exit(1);
+    }
+}
+
+fn parse_args() -> Result<Config, String> {
+    let mut args = std::env::args().skip(1);
+    let mut config = Config {
+        output: args.next().ok_or("missing output path")?,
+        games: 100,
+        nodes: 5_000,
+        processes: 1,
+        seed: 0,
+        worker: None,
+    };
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {flag}"))?;
+        let number = || value.parse::<usize>().map_err(|e| format!("{flag}: {e}"));
+        match flag.as_str() {
+            "--games" => config.games = number()?,
+            "--nodes" => config.nodes = number()?,
+            "--processes" => config.processes = number()?.max(1),
+            "--seed" => config.seed = number()? as u64,
+            "--worker" => config.worker = Some(number()?),
+            _ => return Err(format!("unknown argument: {flag}")),
+        }
+    }
+
+    Ok(config)
+}
+
+fn generate(config: &Config, worker: usize) -> std::io::Result<()> {
+    let path = format!("{}-{worker}", config.output);
+    let mut binary = BufWriter::new(File::create(format!("{path}.bin"))?);
+    let mut text = BufWriter::new(File::create(format!("{path}.txt"))?);
+
+    let mut rng = StdRng::seed_from_u64(config.seed.wrapping_add(worker as u64));
+    let mut engine = Engine::new();
+    engine.silent = true;
+
+    // Spread the remainder over the first workers
+    let games =
+        config.games / config.processes + usize::from(worker < config.games % config.processes);
+    let mut positions = 0;
+
+    for game in 0..games {
+        let (samples, result) = play_game(&mut engine, &mut rng, config.nodes);
+        for sample in &samples {
+            binary.write_all(&pack(&sample.board, sample.score, result))?;
+            writeln!(
+                text,
+                "{};{};{}",
+                sample.board.fen(),
+                sample.score,
+                ["0.0", "0.5", "1.0"][result as usize]
+            )?;
+        }
+
+        positions += samples.len();
+        eprintln!(
+            "worker {worker}: game {}/{games}, {positions} positions",
+            game + 1
+        );
+    }
+
+    binary.flush()?;
+    text.flush()
+}
+
+/// Play a game from a random opening, returning the quiet positions reached and the result as 0,
+/// 1 or 2 for a loss, draw or win for White
+fn play_game(engine: &mut Engine, rng: &mut StdRng, nodes: usize) -> (Vec<Sample>, u8) {
+    let mut board = random_opening(rng, OPENING_PLIES);
+    engine.clear_hash();
+
+    let mut samples = Vec::new();
+    let mut repetitions = HashMap::new();
+    let mut decisive_plies = 0;
+
+    for ply in OPENING_PLIES.. {
+        let moves = board.moves();
+        if moves.is_empty() {
+            let result = match (board.in_check(), board.active_colour) {
+                (false, _) => 1,
+                (true, WHITE) => 0,
+                (true, _) => 2,
+            };
+            return (samples, result);
+        }
+
+        let count = repetitions.entry(board.hash).or_insert(0);
+        *count += 1;
+        if *count >= 3 || board.half_moves >= 100 || ply >= MAX_PLIES || insufficient(&board) {
+            return (samples, 1);
+        }
+
+        engine.reset(Some(board.fen()));
+        let (best_move, score) = engine.search_nodes(nodes);
+        let best_move = best_move.unwrap_or(moves[0]);
+        let white_score = if board.active_colour == WHITE {
+            score
+        } else {
+            -score
+        };
+
+        if white_score.abs() >= ADJUDICATION_SCORE {
+            decisive_plies += 1;
+            if decisive_plies >= ADJUDICATION_PLIES {
+                return (samples, if white_score > 0 { 2 } else { 0 });
+            }
+        } else {
+            decisive_plies = 0;
+        }
+
+        // Only quiet positions can be evaluated statically, so skip checks and tactics
+        let tactical = board.captured_piece(&best_move).is_some() || best_move.promotion.is_some();
+        if ply >= SKIP_PLIES
+            && !board.in_check()
+            && !tactical
+            && white_score.abs() < MAX_RECORDED_SCORE
+        {
+            samples.push(Sample {
+                board: Board::from_fen(&board.fen()).expect("generated fens are valid"),
+                score: white_score,
+            });
+        }
+
+        board.make_move(&best_move);
+    }
+
+    unreachable!("games end by the ply limit at the latest")
+}
+
+fn pack(board: &Board, score: i32, result: u8) -> [u8; 32] {
+    let mut record = [0; 32];
+    let mut occupancy = 0u64;
+    let mut nibbles = Vec::with_capacity(32);
+
+    for (square, piece) in board.squares.iter().enumerate() {
+        if let &Some((colour, piece)) = piece {
+            occupancy |= 1 << square;
+            nibbles.push(colour << 3 | piece);
+        }
+    }
+
+    record[..8].copy_from_slice(&occupancy.to_le_bytes());
+    for (i, pair) in nibbles.chunks(2).enumerate() {
+        record[8 + i] = pair[0] | pair.get(1).map_or(0, |nibble| nibble << 4);
+    }
+    record[24] = board.active_colour;
+    record[25..27].copy_from_slice(&(score as i16).to_le_bytes());
+    record[27] = result;
+
+    record
+}
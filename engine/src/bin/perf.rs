@@ -1,4 +1,5 @@
 #![feature(int_roundings)]
+#![allow(unused_features)]
 use chust_engine::Board;
 use chust_engine::Engine;
 
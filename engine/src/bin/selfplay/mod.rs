@@ -0,0 +1,33 @@
+//! Helpers shared by the tools that play games between engines
+
+use chust_engine::Board;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+/// A position reached by random legal moves from the start that isn't already over
+pub fn random_opening(rng: &mut StdRng, plies: usize) -> Board {
+    loop {
+        let mut board = Board::default();
+        for _ in 0..plies {
+            let moves = board.moves();
+            if moves.is_empty() {
+                break;
+            }
+            board.make_move(&moves[rng.random_range(0..moves.len())]);
+        }
+
+        if !board.moves().is_empty() {
+            return Board::from_fen(&board.fen()).expect("generated fens are valid");
+        }
+    }
+}
+
+/// Whether neither side has enough material left to mate
+pub fn insufficient(board: &Board) -> bool {
+    let [white, black] = board.pieces;
+    // Pawns, rooks and queens can always force mate
+    let majors = |pieces: [u64; 6]| pieces[0] | pieces[3] | pieces[4];
+    let minors = |pieces: [u64; 6]| (pieces[1] | pieces[2]).count_ones();
+
+    majors(white) | majors(black) == 0 && minors(white) <= 1 && minors(black) <= 1
+}
@@ -0,0 +1,193 @@
+//! Tune the search parameters with SPSA over fixed node self-play games
+//!
+//! Usage: `spsa [--iterations N] [--pairs N] [--nodes N] [--rate F] [--seed N]`
+//!
+//! Each iteration perturbs every tunable parameter up or down at random by a step that shrinks
+//! over the run, then plays pairs of games between the engine with the parameters moved one way
+//! and the engine with them moved the other, swapping colours within each pair from the same
+//! random opening. The parameters are then moved towards the side that scored better. The current
+//! values are reported after every iteration, and the final values printed as `setoption`
+//! commands for an engine built with the `tune` feature.
+
+use std::collections::HashMap;
+use std::process::exit;
+
+use chust_engine::{Board, Engine, SearchParams, WHITE};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use selfplay::{insufficient, random_opening};
+
+mod selfplay;
+
+const OPENING_PLIES: usize = 8;
+/// Games still going after this many plies are drawn
+const MAX_PLIES: usize = 300;
+/// Score beyond which a game is adjudicated once it has held for `ADJUDICATION_PLIES`
+const ADJUDICATION_SCORE: i32 = 1_000;
+const ADJUDICATION_PLIES: usize = 6;
+/// Standard exponents for the decay of the step and perturbation sizes
+const ALPHA: f64 = 0.602;
+const GAMMA: f64 = 0.101;
+
+struct Config {
+    iterations: usize,
+    pairs: usize,
+    nodes: usize,
+    /// Step size at the end of the run relative to the perturbation size
+    rate: f64,
+    seed: u64,
+}
+
+fn main() {
+    let config = parse_args().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        eprintln!("usage: spsa [--iterations N] [--pairs N] [--nodes N] [--rate F] [--seed N]");
+        exit(1);
+    });
+
+    let tunables = SearchParams::TUNABLE;
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut values: Vec<f64> = tunables.iter().map(|t| t.default as f64).collect();
+    // Perturb by a twentieth of the range at the end of the run, and at least by one
+    let final_perturbations: Vec<f64> = tunables
+        .iter()
+        .map(|t| ((t.max - t.min) as f64 / 20.0).max(1.0))
+        .collect();
+    let stability = config.iterations as f64 / 10.0;
+
+    let mut plus = Engine::new();
+    let mut minus = Engine::new();
+    plus.silent = true;
+    minus.silent = true;
+
+    for iteration in 1..=config.iterations {
+        let k = iteration as f64;
+        let directions: Vec<f64> = tunables
+            .iter()
+            .map(|_| if rng.random() { 1.0 } else { -1.0 })
+            .collect();
+        let perturbations: Vec<f64> = final_perturbations
+            .iter()
+            .map(|c| c * (config.iterations as f64 / k).powf(GAMMA))
+            .collect();
+
+        for (i, tunable) in tunables.iter().enumerate() {
+            let offset = perturbations[i] * directions[i];
+            let (up, down) = (values[i] + offset, values[i] - offset);
+            plus.params.set(tunable.name, up.round() as i32);
+            minus.params.set(tunable.name, down.round() as i32);
+        }
+
+        let mut score = 0;
+        for _ in 0..config.pairs {
+            let opening = random_opening(&mut rng, OPENING_PLIES).fen();
+            score += play_game(&mut plus, &mut minus, &opening, config.nodes);
+            score -= play_game(&mut minus, &mut plus, &opening, config.nodes);
+        }
+
+        // Step sizes follow the perturbation sizes so every parameter moves at a similar rate
+        // relative to its range
+        let step =
+            config.rate * ((config.iterations as f64 + stability) / (k + stability)).powf(ALPHA);
+        for (i, tunable) in tunables.iter().enumerate() {
+            let gradient = score as f64 / (2.0 * perturbations[i] * directions[i]);
+            values[i] = (values[i] + step * final_perturbations[i].powi(2) * gradient)
+                .clamp(tunable.min as f64, tunable.max as f64);
+        }
+
+        let current: Vec<String> = tunables
+            .iter()
+            .zip(&values)
+            .map(|(t, value)| format!("{}={value:.1}", t.name))
+            .collect();
+        eprintln!(
+            "iteration {iteration}/{}: score {score:+}, {}",
+            config.iterations,
+            current.join(" ")
+        );
+    }
+
+    for (tunable, value) in tunables.iter().zip(&values) {
+        println!("setoption name {} value {}", tunable.name, value.round());
+    }
+}
+
+fn parse_args() -> Result<Config, String> {
+    let mut args = std::env::args().skip(1);
+    let mut config = Config {
+        iterations: 1_000,
+        pairs: 4,
+        nodes: 5_000,
+        rate: 0.002,
+        seed: 0,
+    };
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {flag}"))?;
+        let number = || value.parse::<usize>().map_err(|e| format!("{flag}: {e}"));
+        match flag.as_str() {
+            "--iterations" => config.iterations = number()?.max(1),
+            "--pairs" => config.pairs = number()?.max(1),
+            "--nodes" => config.nodes = number()?,
+            "--rate" => config.rate = value.parse().map_err(|e| format!("{flag}: {e}"))?,
+            "--seed" => config.seed = number()? as u64,
+            _ => return Err(format!("unknown argument: {flag}")),
+        }
+    }
+
+    Ok(config)
+}
+
+/// Play a game from the given position, returning 1, 0 or -1 for a win, draw or loss for the
+/// engine playing White
+fn play_game(white: &mut Engine, black: &mut Engine, fen: &str, nodes: usize) -> i32 {
+    let mut board = Board::from_fen(fen).expect("openings are valid positions");
+    white.clear_hash();
+    black.clear_hash();
+
+    let mut repetitions = HashMap::new();
+    let mut decisive_plies = 0;
+
+    for ply in OPENING_PLIES.. {
+        let moves = board.moves();
+        if moves.is_empty() {
+            return match (board.in_check(), board.active_colour) {
+                (false, _) => 0,
+                (true, WHITE) => -1,
+                (true, _) => 1,
+            };
+        }
+
+        let count = repetitions.entry(board.hash).or_insert(0);
+        *count += 1;
+        if *count >= 3 || board.half_moves >= 100 || ply >= MAX_PLIES || insufficient(&board) {
+            return 0;
+        }
+
+        let engine = if board.active_colour == WHITE {
+            &mut *white
+        } else {
+            &mut *black
+        };
+        engine.reset(Some(board.fen()));
+        let (best_move, score) = engine.search_nodes(nodes);
+
+        if score.abs() >= ADJUDICATION_SCORE {
+            decisive_plies += 1;
+            if decisive_plies >= ADJUDICATION_PLIES {
+                let white_score = if board.active_colour == WHITE {
+                    score
+                } else {
+                    -score
+                };
+                return white_score.signum();
+            }
+        } else {
+            decisive_plies = 0;
+        }
+
+        board.make_move(&best_move.unwrap_or(moves[0]));
+    }
+
+    unreachable!("games end by the ply limit at the latest")
+}
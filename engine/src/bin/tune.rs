@@ -0,0 +1,283 @@
+//! Tune the handcrafted evaluation weights on labelled positions with Texel's method
+//!
+//! Usage: `tune <dataset> [--epochs N] [--rate F] [--params file] [--output file]`
+//!
+//! The dataset has one position per line as `fen;...;result`, such as the text output of
+//! `datagen`, where the result is 1.0, 0.5 or 0.0 for White. The evaluation is linearised around
+//! the starting weights by measuring how each weight moves the midgame and endgame scores of every
+//! position, which is exact for all but the king attack terms, and keeping the endgame scale
+//! factor each position starts with. Positions scored by a specialised endgame evaluator rather
+//! than the weights are skipped. The sigmoid scaling constant is fitted to the starting
+//! evaluation, then the weights are trained with Adam to minimise the mean squared error between
+//! the predicted and actual results. The tuned weights are saved to the output file, which can be
+//! passed back as `--params` to relinearise and keep tuning, and printed as Rust constants to
+//! replace those in `calculated::values`.
+
+use std::process::exit;
+
+use chust_engine::{Board, Engine, EvalParams, SCALE_NORMAL, TOTAL_PHASE};
+
+const BETA1: f64 = 0.9;
+const BETA2: f64 = 0.999;
+const EPSILON: f64 = 1e-8;
+
+struct Config {
+    dataset: String,
+    epochs: usize,
+    rate: f64,
+    params: Option<String>,
+    output: String,
+}
+
+struct Position {
+    /// White's midgame and endgame scores with the starting weights
+    midgame: f64,
+    endgame: f64,
+    phase: f64,
+    /// Share of the endgame score kept for the material left
+    scale: f64,
+    result: f64,
+    /// Change in the midgame and endgame scores per unit of each weight that affects them
+    coefficients: Vec<(usize, f64, f64)>,
+}
+
+impl Position {
+    /// Evaluation from White's point of view with weights moved by the given deltas
+    fn evaluate(&self, deltas: &[f64]) -> f64 {
+        let (mut midgame, mut endgame) = (self.midgame, self.endgame);
+        for &(index, mg, eg) in &self.coefficients {
+            midgame += mg * deltas[index];
+            endgame += eg * deltas[index];
+        }
+        let total = TOTAL_PHASE as f64;
+        (midgame * self.phase + endgame * self.scale * (total - self.phase)) / total
+    }
+}
+
+fn main() {
+    let config = parse_args().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        eprintln!("usage: tune <dataset> [--epochs N] [--rate F] [--params file] [--output file]");
+        exit(1);
+    });
+
+    let params = match &config.params {
+        Some(path) => EvalParams::load(path).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            exit(1);
+        }),
+        None => EvalParams::default(),
+    };
+
+    let boards = load_dataset(&config.dataset).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        exit(1);
+    });
+    eprintln!("loaded {} positions", boards.len());
+
+    let positions = linearise(&boards, &params);
+    let initial = params.to_vec();
+    let mut deltas = vec![0.0; initial.len()];
+
+    let k = fit_scaling(&positions, &deltas);
+    eprintln!("K = {k:.4}, loss = {:.6}", loss(&positions, &deltas, k));
+
+    let mut first_moment = vec![0.0; deltas.len()];
+    let mut second_moment = vec![0.0; deltas.len()];
+    for epoch in 1..=config.epochs {
+        let gradient = gradient(&positions, &deltas, k);
+        for i in 0..deltas.len() {
+            first_moment[i] = BETA1 * first_moment[i] + (1.0 - BETA1) * gradient[i];
+            second_moment[i] = BETA2 * second_moment[i] + (1.0 - BETA2) * gradient[i].powi(2);
+            let first = first_moment[i] / (1.0 - BETA1.powi(epoch as i32));
+            let second = second_moment[i] / (1.0 - BETA2.powi(epoch as i32));
+            deltas[i] -= config.rate * first / (second.sqrt() + EPSILON);
+        }
+
+        if epoch % 50 == 0 || epoch == config.epochs {
+            eprintln!("epoch {epoch}: loss = {:.6}", loss(&positions, &deltas, k));
+        }
+    }
+
+    let tuned: Vec<i32> = initial
+        .iter()
+        .zip(&deltas)
+        .map(|(&weight, delta)| weight + delta.round() as i32)
+        .collect();
+    let mut params = params;
+    params.set_from_slice(&tuned);
+
+    if let Err(e) = params.save(&config.output) {
+        eprintln!("{e}");
+        exit(1);
+    }
+    print!("{}", params.to_rust());
+}
+
+fn parse_args() -> Result<Config, String> {
+    let mut args = std::env::args().skip(1);
+    let mut config = Config {
+        dataset: args.next().ok_or("missing dataset path")?,
+        epochs: 1_000,
+        rate: 1.0,
+        params: None,
+        output: "tuned.txt".to_string(),
+    };
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--epochs" => config.epochs = value.parse().map_err(|e| format!("{flag}: {e}"))?,
+            "--rate" => config.rate = value.parse().map_err(|e| format!("{flag}: {e}"))?,
+            "--params" => config.params = Some(value),
+            "--output" => config.output = value,
+            _ => return Err(format!("unknown argument: {flag}")),
+        }
+    }
+
+    Ok(config)
+}
+
+/// Positions and results from a dataset, leaving out those the weights play no part in
+fn load_dataset(path: &str) -> Result<Vec<(Board, f64)>, String> {
+    let dataset = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let positions: Vec<(Board, f64)> = dataset
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let invalid = || format!("{path}:{}: invalid position", number + 1);
+            let (fen, result) = line
+                .split_once(';')
+                .and_then(|(fen, rest)| Some((fen, rest.rsplit(';').next()?)))
+                .ok_or_else(invalid)?;
+            let board = Board::from_fen(fen.trim()).map_err(|_| invalid())?;
+            let result = result.trim().parse().map_err(|_| invalid())?;
+            Ok((board, result))
+        })
+        .collect::<Result<_, String>>()?;
+
+    let mut engine = Engine::new();
+    let total = positions.len();
+    let positions: Vec<_> = positions
+        .into_iter()
+        .filter(|(board, _)| {
+            engine.board = board.clone();
+            engine.trace().endgame.is_none()
+        })
+        .collect();
+    eprintln!(
+        "skipped {} positions with specialised endgame evaluators",
+        total - positions.len()
+    );
+    Ok(positions)
+}
+
+/// Evaluate every position with the starting weights and with each weight in turn increased by
+/// one, recording the weights that change each evaluation
+fn linearise(boards: &[(Board, f64)], params: &EvalParams) -> Vec<Position> {
+    let mut engine = Engine::new();
+    engine.set_eval_params(params.clone());
+
+    let mut positions: Vec<Position> = boards
+        .iter()
+        .map(|(board, result)| {
+            let (midgame, endgame) = scores(&mut engine, board);
+            Position {
+                midgame: midgame as f64,
+                endgame: endgame as f64,
+                phase: board.phase() as f64,
+                scale: engine.trace().scale as f64 / SCALE_NORMAL as f64,
+                result: *result,
+                coefficients: Vec::new(),
+            }
+        })
+        .collect();
+
+    let weights = params.to_vec();
+    for index in 0..weights.len() {
+        let mut perturbed = weights.clone();
+        perturbed[index] += 1;
+        let mut params = params.clone();
+        params.set_from_slice(&perturbed);
+        engine.set_eval_params(params);
+
+        for (position, (board, _)) in positions.iter_mut().zip(boards) {
+            let (midgame, endgame) = scores(&mut engine, board);
+            let (mg, eg) = (
+                midgame as f64 - position.midgame,
+                endgame as f64 - position.endgame,
+            );
+            if mg != 0.0 || eg != 0.0 {
+                position.coefficients.push((index, mg, eg));
+            }
+        }
+
+        if (index + 1) % 100 == 0 || index + 1 == weights.len() {
+            eprintln!("linearised {}/{} weights", index + 1, weights.len());
+        }
+    }
+
+    positions
+}
+
+/// White's midgame and endgame scores of a position
+fn scores(engine: &mut Engine, board: &Board) -> (i32, i32) {
+    engine.board = board.clone();
+    let [white, black] = engine.trace().totals();
+    (white.0 - black.0, white.1 - black.1)
+}
+
+/// Expected result for White of a position with the given evaluation
+fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+fn loss(positions: &[Position], deltas: &[f64], k: f64) -> f64 {
+    positions
+        .iter()
+        .map(|position| (position.result - sigmoid(position.evaluate(deltas), k)).powi(2))
+        .sum::<f64>()
+        / positions.len() as f64
+}
+
+/// Scaling constant minimising the loss, found by ternary search as the loss is unimodal in it
+fn fit_scaling(positions: &[Position], deltas: &[f64]) -> f64 {
+    let (mut low, mut high) = (0.0, 10.0);
+    for _ in 0..100 {
+        let a = low + (high - low) / 3.0;
+        let b = high - (high - low) / 3.0;
+        if loss(positions, deltas, a) < loss(positions, deltas, b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    (low + high) / 2.0
+}
+
+fn gradient(positions: &[Position], deltas: &[f64], k: f64) -> Vec<f64> {
+    let mut gradient = vec![0.0; deltas.len()];
+    for position in positions {
+        let prediction = sigmoid(position.evaluate(deltas), k);
+        // Derivative of the squared error with respect to the evaluation
+        let error = 2.0
+            * (prediction - position.result)
+            * prediction
+            * (1.0 - prediction)
+            * k
+            * std::f64::consts::LN_10
+            / 400.0;
+
+        let midgame = position.phase / TOTAL_PHASE as f64;
+        let endgame = (1.0 - midgame) * position.scale;
+        for &(index, mg, eg) in &position.coefficients {
+            gradient[index] += error * (mg * midgame + eg * endgame);
+        }
+    }
+
+    for value in &mut gradient {
+        *value /= positions.len() as f64;
+    }
+    gradient
+}
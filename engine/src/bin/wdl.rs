@@ -0,0 +1,164 @@
+//! Fit the win, draw and loss model used for `UCI_ShowWDL` to self-play results
+//!
+//! Usage: `wdl <dataset>...`
+//!
+//! Each dataset has one position per line as `fen;score;result`, such as the text output of
+//! `datagen`, with the score in centipawns and the result 1.0, 0.5 or 0.0 for White. Positions
+//! are counted by score to the nearest 10 centipawns, material and result, then the centre and
+//! scale of the win curve and how each changes with material are fitted by maximum likelihood
+//! with the Nelder-Mead method. The fitted parameters are printed as Rust constants to replace
+//! those in `engine::wdl`.
+
+use std::collections::HashMap;
+use std::process::exit;
+
+use chust_engine::{Board, wdl_material};
+
+/// Width in centipawns of the score buckets positions are counted in
+const SCORE_BUCKET: i32 = 10;
+/// Starting centre and scale at no material and per point of material, and the size of the first
+/// step taken in each
+const START: [f64; 4] = [150.0, 2.0, 60.0, 1.0];
+const STEP: [f64; 4] = [30.0, 1.0, 20.0, 0.5];
+const ITERATIONS: usize = 1_000;
+/// Smallest chance given to any result, so a single surprising game can't dominate the fit
+const MIN_CHANCE: f64 = 1e-9;
+
+/// Number of positions with each score bucket, material and result, the result in half points
+type Counts = HashMap<(i32, i32, u8), usize>;
+
+fn main() {
+    let paths: Vec<String> = std::env::args().skip(1).collect();
+    if paths.is_empty() {
+        eprintln!("usage: wdl <dataset>...");
+        exit(1);
+    }
+
+    let mut counts = Counts::new();
+    for path in &paths {
+        if let Err(e) = count(path, &mut counts) {
+            eprintln!("{e}");
+            exit(1);
+        }
+    }
+    let positions: usize = counts.values().sum();
+    eprintln!("loaded {positions} positions in {} buckets", counts.len());
+
+    let counts: Vec<_> = counts.into_iter().collect();
+    let (params, loss) = nelder_mead(|params| negative_log_likelihood(&counts, params));
+    eprintln!("mean log loss = {:.6}", loss / positions as f64);
+
+    let [centre, centre_material, scale, scale_material] = params;
+    println!("const CENTRE: (f64, f64) = ({centre:.1}, {centre_material:.2});");
+    println!("const SCALE: (f64, f64) = ({scale:.1}, {scale_material:.2});");
+}
+
+fn count(path: &str, counts: &mut Counts) -> Result<(), String> {
+    let dataset = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    for (number, line) in dataset.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let invalid = || format!("{path}:{}: invalid position", number + 1);
+        let mut fields = line.split(';');
+        let (Some(fen), Some(score), Some(result)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+        let board = Board::from_fen(fen.trim()).map_err(|_| invalid())?;
+        let score: i32 = score.trim().parse().map_err(|_| invalid())?;
+        let result: f64 = result.trim().parse().map_err(|_| invalid())?;
+
+        let bucket = (score as f64 / SCORE_BUCKET as f64).round() as i32 * SCORE_BUCKET;
+        let key = (bucket, wdl_material(&board), (result * 2.0).round() as u8);
+        *counts.entry(key).or_default() += 1;
+    }
+    Ok(())
+}
+
+/// Negative log likelihood of the results given the model's parameters
+fn negative_log_likelihood(counts: &[((i32, i32, u8), usize)], params: [f64; 4]) -> f64 {
+    let [centre, centre_material, scale, scale_material] = params;
+    let mut total = 0.0;
+    for &((score, material, result), count) in counts {
+        let centre = centre + centre_material * material as f64;
+        let scale = scale + scale_material * material as f64;
+        if centre <= 0.0 || scale <= 1.0 {
+            return f64::INFINITY;
+        }
+
+        let chance = |score: f64| 1.0 / (1.0 + ((centre - score) / scale).clamp(-50.0, 50.0).exp());
+        let (win, loss) = (chance(score as f64), chance(-score as f64));
+        let chance = match result {
+            2 => win,
+            0 => loss,
+            _ => 1.0 - win - loss,
+        };
+        total -= count as f64 * chance.max(MIN_CHANCE).ln();
+    }
+    total
+}
+
+/// Minimise a function of the parameters with the Nelder-Mead simplex method, returning the best
+/// parameters found along with their value
+fn nelder_mead(f: impl Fn([f64; 4]) -> f64) -> ([f64; 4], f64) {
+    let towards = |from: [f64; 4], to: [f64; 4], amount: f64| -> [f64; 4] {
+        std::array::from_fn(|i| from[i] + amount * (to[i] - from[i]))
+    };
+
+    let mut simplex: Vec<([f64; 4], f64)> = (0..=START.len())
+        .map(|i| {
+            let mut point = START;
+            if i > 0 {
+                point[i - 1] += STEP[i - 1];
+            }
+            (point, f(point))
+        })
+        .collect();
+
+    for _ in 0..ITERATIONS {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (worst, worst_value) = simplex[START.len()];
+        let second_worst = simplex[START.len() - 1].1;
+        let centroid: [f64; 4] = std::array::from_fn(|i| {
+            simplex[..START.len()]
+                .iter()
+                .map(|(point, _)| point[i])
+                .sum::<f64>()
+                / START.len() as f64
+        });
+
+        let reflected = towards(centroid, worst, -1.0);
+        let reflected_value = f(reflected);
+        if reflected_value < simplex[0].1 {
+            let expanded = towards(centroid, worst, -2.0);
+            let expanded_value = f(expanded);
+            simplex[START.len()] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+        } else if reflected_value < second_worst {
+            simplex[START.len()] = (reflected, reflected_value);
+        } else {
+            let contracted = towards(centroid, worst, 0.5);
+            let contracted_value = f(contracted);
+            if contracted_value < worst_value {
+                simplex[START.len()] = (contracted, contracted_value);
+            } else {
+                // Shrink everything towards the best point
+                let best = simplex[0].0;
+                for (point, value) in &mut simplex[1..] {
+                    *point = towards(best, *point, 0.5);
+                    *value = f(*point);
+                }
+            }
+        }
+    }
+
+    simplex
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .expect("the simplex has a point for each parameter and one more")
+}
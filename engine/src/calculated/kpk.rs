@@ -0,0 +1,163 @@
+//! Bitbase of king and pawn against king positions, built by retrograde analysis
+//!
+//! Positions are stored with the pawn belonging to White and on the queen side, which every other
+//! position can be mirrored into.
+
+use std::sync::LazyLock;
+
+use bitvec::prelude::*;
+
+use super::king::KING_MOVES;
+use super::pawn::PAWN_ATTACKS;
+use crate::repr::bitboards::distance;
+use crate::repr::constants::*;
+
+/// Side to move, both kings and a pawn on one of the files a to d and ranks 2 to 7
+const POSITIONS: usize = 2 * 64 * 64 * 24;
+
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+/// Whether White wins with the given side to move, White king, White pawn and Black king, with
+/// the pawn on one of the files a to d
+pub fn probe(colour: u8, white_king: u8, pawn: u8, black_king: u8) -> bool {
+    debug_assert!(pawn % 8 <= 3, "pawn should be on the queen side");
+    KPK[index(colour, white_king, pawn, black_king)]
+}
+
+fn index(colour: u8, white_king: u8, pawn: u8, black_king: u8) -> usize {
+    white_king as usize
+        | (black_king as usize) << 6
+        | (colour as usize) << 12
+        | (pawn as usize % 8) << 13
+        | (6 - pawn as usize / 8) << 15
+}
+
+static KPK: LazyLock<BitVec> = LazyLock::new(|| {
+    let mut results: Vec<u8> = (0..POSITIONS).map(initial_result).collect();
+
+    // Keep resolving positions from their successors until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..POSITIONS {
+            if results[i] == UNKNOWN {
+                results[i] = classify(i, &results);
+                changed |= results[i] != UNKNOWN;
+            }
+        }
+    }
+
+    results.iter().map(|&result| result == WIN).collect()
+});
+
+/// Side to move, White king, pawn and Black king of an index
+fn decode(i: usize) -> (u8, u8, u8, u8) {
+    let white_king = (i & 0x3f) as u8;
+    let black_king = (i >> 6 & 0x3f) as u8;
+    let colour = (i >> 12 & 1) as u8;
+    let pawn = ((6 - (i >> 15 & 7)) * 8 + (i >> 13 & 3)) as u8;
+    (colour, white_king, pawn, black_king)
+}
+
+/// Result of a position that can be decided without looking at its successors
+fn initial_result(i: usize) -> u8 {
+    let (colour, white_king, pawn, black_king) = decode(i);
+    let white_attacks = KING_MOVES[white_king as usize];
+    let black_attacks = KING_MOVES[black_king as usize];
+    let pawn_attacks = PAWN_ATTACKS[WHITE as usize][pawn as usize];
+    let stop = pawn + 8;
+
+    if distance(white_king, black_king) <= 1
+        || white_king == pawn
+        || black_king == pawn
+        || (colour == WHITE && pawn_attacks & 1 << black_king != 0)
+    {
+        INVALID
+    } else if colour == WHITE
+        && pawn / 8 == 6
+        && white_king != stop
+        && black_king != stop
+        && (distance(black_king, stop) > 1 || distance(white_king, stop) == 1)
+    {
+        // The pawn promotes without the queen being taken
+        WIN
+    } else if colour == BLACK
+        && (black_attacks & !(white_attacks | pawn_attacks) == 0
+            || black_attacks & !white_attacks & 1 << pawn != 0)
+    {
+        // Stalemate, or the pawn can be taken
+        DRAW
+    } else {
+        UNKNOWN
+    }
+}
+
+/// Result of a position from the results of its successors, where White needs one successor to
+/// win and Black one successor to draw
+fn classify(i: usize, results: &[u8]) -> u8 {
+    let (colour, white_king, pawn, black_king) = decode(i);
+    let (good, bad) = if colour == WHITE {
+        (WIN, DRAW)
+    } else {
+        (DRAW, WIN)
+    };
+
+    let mut successors = INVALID;
+    let king = if colour == WHITE {
+        white_king
+    } else {
+        black_king
+    };
+    let mut moves = KING_MOVES[king as usize];
+    while moves != 0 {
+        let destination = moves.trailing_zeros() as u8;
+        moves &= moves - 1;
+
+        successors |= if colour == WHITE {
+            results[index(BLACK, destination, pawn, black_king)]
+        } else {
+            results[index(WHITE, white_king, pawn, destination)]
+        };
+    }
+
+    if colour == WHITE {
+        if pawn / 8 < 6 {
+            successors |= results[index(BLACK, white_king, pawn + 8, black_king)];
+        }
+        let stop = pawn + 8;
+        if pawn / 8 == 1 && stop != white_king && stop != black_king {
+            successors |= results[index(BLACK, white_king, pawn + 16, black_king)];
+        }
+    }
+
+    if successors & good != 0 {
+        good
+    } else if successors & UNKNOWN != 0 {
+        UNKNOWN
+    } else {
+        bad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_positions() {
+        // The king on the sixth rank in front of its pawn wins whoever is to move
+        assert!(probe(WHITE, D6, D5, D8));
+        assert!(probe(BLACK, D6, D5, D8));
+        // Further back it needs the opposition
+        assert!(probe(BLACK, D5, D4, D7));
+        assert!(!probe(WHITE, D5, D4, D7));
+        // A rook pawn is drawn once the defending king reaches the corner
+        assert!(!probe(WHITE, B6, A5, A8));
+        // The defending king can only catch a pawn from inside its square
+        assert!(probe(BLACK, H1, B5, H5));
+        assert!(!probe(BLACK, H1, B5, D6));
+    }
+}
@@ -2,12 +2,15 @@ pub use bishop::generate_bishop_moves;
 pub use king::{KING_MOVES, generate_king_moves};
 pub use knight::generate_knight_moves;
 pub use pawn::{PAWN_ATTACKS, generate_pawn_moves};
+pub use rays::{between, line};
 pub use rook::generate_rook_moves;
 
+pub mod kpk;
 pub mod values;
 
 mod bishop;
 mod king;
 mod knight;
 mod pawn;
+mod rays;
 mod rook;
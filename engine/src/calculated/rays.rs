@@ -0,0 +1,77 @@
+use std::sync::LazyLock;
+
+use super::{generate_bishop_moves, generate_rook_moves};
+use crate::repr::constants::*;
+
+/// Squares strictly between two squares sharing a rank, file or diagonal, and empty otherwise
+pub fn between(a: u8, b: u8) -> u64 {
+    BETWEEN[a as usize][b as usize]
+}
+
+/// Every square on the rank, file or diagonal through two squares, and empty if there is none
+pub fn line(a: u8, b: u8) -> u64 {
+    LINE[a as usize][b as usize]
+}
+
+static BETWEEN: LazyLock<Box<[[u64; 64]; 64]>> = LazyLock::new(|| {
+    let mut between = Box::new([[0; 64]; 64]);
+
+    for a in A1..=H8 {
+        for b in A1..=H8 {
+            let (a_bit, b_bit) = (1 << a, 1 << b);
+
+            between[a as usize][b as usize] = if generate_rook_moves(a, 0) & b_bit != 0 {
+                generate_rook_moves(a, b_bit) & generate_rook_moves(b, a_bit)
+            } else if generate_bishop_moves(a, 0) & b_bit != 0 {
+                generate_bishop_moves(a, b_bit) & generate_bishop_moves(b, a_bit)
+            } else {
+                0
+            };
+        }
+    }
+
+    between
+});
+
+static LINE: LazyLock<Box<[[u64; 64]; 64]>> = LazyLock::new(|| {
+    let mut line = Box::new([[0; 64]; 64]);
+
+    for a in A1..=H8 {
+        for b in A1..=H8 {
+            let (a_bit, b_bit) = (1 << a, 1 << b);
+
+            line[a as usize][b as usize] = if generate_rook_moves(a, 0) & b_bit != 0 {
+                generate_rook_moves(a, 0) & generate_rook_moves(b, 0) | a_bit | b_bit
+            } else if generate_bishop_moves(a, 0) & b_bit != 0 {
+                generate_bishop_moves(a, 0) & generate_bishop_moves(b, 0) | a_bit | b_bit
+            } else {
+                0
+            };
+        }
+    }
+
+    line
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn between_squares() {
+        assert_eq!(between(A1, A4), 1 << A2 | 1 << A3);
+        assert_eq!(between(A4, A1), 1 << A2 | 1 << A3);
+        assert_eq!(between(B2, E5), 1 << C3 | 1 << D4);
+        assert_eq!(between(H1, E4), 1 << G2 | 1 << F3);
+        assert_eq!(between(C1, C2), 0);
+        assert_eq!(between(A1, B3), 0);
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(line(B1, G1), 0xff);
+        assert_eq!(line(C3, E5), 0x8040201008040201);
+        assert_eq!(line(E4, E6), 0x1010101010101010);
+        assert_eq!(line(A1, B3), 0);
+    }
+}
@@ -1,5 +1,7 @@
 //! Various statically defined values for use in search and evaluation
 
+use std::time::Duration;
+
 // Engine Values
 pub const QUEEN_VALUE: i32 = 900;
 pub const ROOK_VALUE: i32 = 500;
@@ -7,6 +9,37 @@ pub const BISHOP_VALUE: i32 = 350;
 pub const KNIGHT_VALUE: i32 = 300;
 pub const PAWN_VALUE: i32 = 100;
 pub const MATE_VALUE: i32 = 10_000;
+/// Material values indexed by piece type, the king is never captured
+pub const PIECE_VALUES: [i32; 6] = [
+    PAWN_VALUE,
+    KNIGHT_VALUE,
+    BISHOP_VALUE,
+    ROOK_VALUE,
+    QUEEN_VALUE,
+    0,
+];
+
+// Search Values
+pub const INFINITY: i32 = 20_000;
+/// Extensions are only granted while the ply is below this multiple of the root depth
+pub const EXTENSION_LIMIT: usize = 2;
+/// Nodes searched between checks of the clock
+pub const TIME_CHECK_NODES: usize = 1024;
+/// Time into a search after which the root move being searched is reported
+pub const CURRMOVE_DELAY: Duration = Duration::from_secs(3);
+/// Time between checks for `stop` while a finished search holds back its move
+pub const HOLD_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+// Move Ordering Scores
+pub const TT_MOVE_SCORE: i32 = 3_000_000;
+pub const PV_MOVE_SCORE: i32 = 2_000_000;
+pub const GOOD_CAPTURE_SCORE: i32 = 1_000_000;
+pub const FIRST_KILLER_SCORE: i32 = 900_000;
+pub const SECOND_KILLER_SCORE: i32 = 800_000;
+pub const COUNTER_MOVE_SCORE: i32 = 700_000;
+pub const BAD_CAPTURE_SCORE: i32 = -1_000_000;
+/// Bound on the magnitude of each history table entry
+pub const MAX_HISTORY: i32 = 16_384;
 
 /// Most valuable victim - least valuable attacker: [attacker][victim]
 pub const MVV_LVA: [[i32; 6]; 6] = [
@@ -18,62 +51,264 @@ pub const MVV_LVA: [[i32; 6]; 6] = [
     [100, 200, 300, 400, 500, 600],
 ];
 
+// Evaluation Values
+/// Midgame material values indexed by piece type
+pub const MG_PIECE_VALUES: [i32; 6] = [82, 337, 365, 477, 1025, 0];
+/// Endgame material values indexed by piece type
+pub const EG_PIECE_VALUES: [i32; 6] = [94, 281, 297, 512, 936, 0];
+/// Contribution of each piece type to the game phase
+pub const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+/// Game phase of the starting position, anything at or above this is pure midgame
+pub const TOTAL_PHASE: i32 = 24;
+
+// Pawn structure terms as (midgame, endgame) pairs, with per-rank terms indexed by the rank
+// relative to the pawn's own side
+pub const PASSED_PAWN: [(i32, i32); 8] = [
+    (0, 0),
+    (0, 5),
+    (5, 10),
+    (10, 20),
+    (25, 40),
+    (45, 70),
+    (70, 110),
+    (0, 0),
+];
+/// Extra endgame bonus for a passed pawn whose path to promotion is empty and unattacked
+pub const FREE_PASSED_PAWN: [i32; 8] = [0, 0, 0, 10, 20, 40, 70, 0];
+pub const BLOCKED_PASSED_PAWN: (i32, i32) = (-5, -15);
+/// Endgame bonus per square of distance between the stop square of a passed pawn and each king,
+/// scaled by how far the pawn has advanced
+pub const PASSED_PAWN_OWN_KING_DISTANCE: i32 = -2;
+pub const PASSED_PAWN_ENEMY_KING_DISTANCE: i32 = 5;
+pub const DOUBLED_PAWN: (i32, i32) = (-10, -25);
+pub const ISOLATED_PAWN: (i32, i32) = (-10, -15);
+pub const BACKWARD_PAWN: (i32, i32) = (-8, -12);
+/// Bonus for a pawn standing beside a friendly pawn
+pub const PHALANX_PAWN: [(i32, i32); 8] = [
+    (0, 0),
+    (0, 0),
+    (3, 2),
+    (6, 4),
+    (10, 8),
+    (20, 15),
+    (35, 30),
+    (0, 0),
+];
+/// Bonus for a pawn defended by a friendly pawn, forming a chain
+pub const SUPPORTED_PAWN: (i32, i32) = (8, 6);
+
+// Piece terms as (midgame, endgame) pairs
+/// Bonus per safe square a piece attacks beyond its typical number, indexed by piece type
+pub const MOBILITY: [(i32, i32); 6] = [(0, 0), (4, 4), (5, 5), (2, 4), (1, 2), (0, 0)];
+/// Typical number of safe squares attacked by each piece type, below which mobility is a penalty
+pub const MOBILITY_BASELINE: [i32; 6] = [0, 4, 6, 7, 13, 0];
+pub const BISHOP_PAIR: (i32, i32) = (30, 50);
+pub const ROOK_OPEN_FILE: (i32, i32) = (25, 10);
+pub const ROOK_SEMI_OPEN_FILE: (i32, i32) = (12, 7);
+/// Bonus for a rook on the seventh rank while the enemy king is on the eighth or pawns remain on
+/// the seventh
+pub const ROOK_ON_SEVENTH: (i32, i32) = (10, 25);
+/// Bonus for a knight defended by a pawn that no enemy pawn can ever attack
+pub const KNIGHT_OUTPOST: (i32, i32) = (25, 15);
+/// Penalty for a rook shut in on the back rank by its own uncastled king
+pub const TRAPPED_ROOK: (i32, i32) = (-40, -10);
+/// Penalty for a bishop that captured on a7 or h7 and is shut in by a pawn on b6 or g6
+pub const TRAPPED_BISHOP: (i32, i32) = (-100, -100);
+
+// King safety terms, all of which only apply in the midgame
+/// Bonus for a pawn sheltering the king, indexed by how many ranks in front of the king it stands
+pub const PAWN_SHIELD: [i32; 8] = [0, 20, 10, 0, 0, 0, 0, 0];
+/// Penalty for a file next to the king with no pawn to shelter it
+pub const MISSING_SHIELD_PAWN: i32 = -15;
+/// Penalty for an enemy pawn advancing on the king, indexed by how many ranks in front it stands
+pub const PAWN_STORM: [i32; 8] = [0, -10, -30, -20, -10, 0, 0, 0];
+pub const KING_SEMI_OPEN_FILE: i32 = -15;
+pub const KING_OPEN_FILE: i32 = -25;
+/// Attack units for each square of the king zone a piece attacks, indexed by piece type
+pub const KING_ATTACK_WEIGHTS: [i32; 6] = [0, 2, 2, 3, 5, 0];
+/// Attack units for a piece being able to give check from a square not covered by pawns
+pub const SAFE_CHECK_WEIGHTS: [i32; 6] = [0, 6, 4, 8, 10, 0];
+/// Attack units are squared and divided by this to get the king danger penalty
+pub const KING_DANGER_DIVISOR: i32 = 4;
+pub const MAX_KING_DANGER: i32 = 600;
+
+// Endgame terms
+/// Bonus on top of material for an endgame known to be won, well clear of mate scores
+pub const KNOWN_WIN: i32 = 2_000;
+/// Scale factor out of which the endgame score is scaled down for drawish material
+pub const SCALE_NORMAL: i32 = 64;
+/// Scale factor for opposite coloured bishops with only pawns besides
+pub const SCALE_OPPOSITE_BISHOPS: i32 = 16;
+/// Scale factor for opposite coloured bishops alongside other pieces
+pub const SCALE_OPPOSITE_BISHOPS_WITH_PIECES: i32 = 40;
+/// Scale factor for a rook and pawn against a rook with the defending king in front of the pawn
+pub const SCALE_ROOK_PAWN_FORTRESS: i32 = 8;
+/// Scale factor for a rook ending with all the pawns on one side of the board and at most one
+/// pawn between them
+pub const SCALE_ROOK_ONE_SIDE: i32 = 48;
+
+// Piece-square tables, laid out as seen from White's side with A8 first, so White indexes them by
+// `square ^ 56` and Black by `square`
+
+#[rustfmt::skip]
+pub const MG_PAWN_SCORE: [i32; 64] = [
+      0,    0,    0,    0,    0,    0,    0,    0,
+     98,  134,   61,   95,   68,  126,   34,  -11,
+     -6,    7,   26,   31,   65,   56,   25,  -20,
+    -14,   13,    6,   21,   23,   12,   17,  -23,
+    -27,   -2,   -5,   12,   17,    6,   10,  -25,
+    -26,   -4,   -4,  -10,    3,    3,   33,  -12,
+    -35,   -1,  -20,  -23,  -15,   24,   38,  -22,
+      0,    0,    0,    0,    0,    0,    0,    0
+];
+
+#[rustfmt::skip]
+pub const EG_PAWN_SCORE: [i32; 64] = [
+      0,    0,    0,    0,    0,    0,    0,    0,
+    178,  173,  158,  134,  147,  132,  165,  187,
+     94,  100,   85,   67,   56,   53,   82,   84,
+     32,   24,   13,    5,   -2,    4,   17,   17,
+     13,    9,   -3,   -7,   -7,   -8,    3,   -1,
+      4,    7,   -6,    1,    0,   -5,   -1,   -8,
+     13,    8,    8,   10,   13,    0,    2,   -7,
+      0,    0,    0,    0,    0,    0,    0,    0
+];
+
 #[rustfmt::skip]
-pub const PAWN_SCORE: [i32; 64] = [
-    90,  90,  90,  90,  90,  90,  90,  90,
-    30,  30,  30,  40,  40,  30,  30,  30,
-    20,  20,  20,  30,  30,  30,  20,  20,
-    10,  10,  10,  20,  20,  10,  10,  10,
-     5,   5,  10,  20,  20,   5,   5,   5,
-     0,   0,   0,   5,   5,   0,   0,   0,
-     0,   0,   0, -10, -10,   0,   0,   0,
-     0,   0,   0,   0,   0,   0,   0,   0
+pub const MG_KNIGHT_SCORE: [i32; 64] = [
+   -167,  -89,  -34,  -49,   61,  -97,  -15, -107,
+    -73,  -41,   72,   36,   23,   62,    7,  -17,
+    -47,   60,   37,   65,   84,  129,   73,   44,
+     -9,   17,   19,   53,   37,   69,   18,   22,
+    -13,    4,   16,   13,   28,   19,   21,   -8,
+    -23,   -9,   12,   10,   19,   17,   25,  -16,
+    -29,  -53,  -12,   -3,   -1,   18,  -14,  -19,
+   -105,  -21,  -58,  -33,  -17,  -28,  -19,  -23
 ];
 
 #[rustfmt::skip]
-pub const KNIGHT_SCORE: [i32; 64] = [
-    -5,   0,   0,   0,   0,   0,   0,  -5,
-    -5,   0,   0,  10,  10,   0,   0,  -5,
-    -5,   5,  20,  20,  20,  20,   5,  -5,
-    -5,  10,  20,  30,  30,  20,  10,  -5,
-    -5,  10,  20,  30,  30,  20,  10,  -5,
-    -5,   5,  20,  10,  10,  20,   5,  -5,
-    -5,   0,   0,   0,   0,   0,   0,  -5,
-    -5, -10,   0,   0,   0,   0, -10,  -5
+pub const EG_KNIGHT_SCORE: [i32; 64] = [
+    -58,  -38,  -13,  -28,  -31,  -27,  -63,  -99,
+    -25,   -8,  -25,   -2,   -9,  -25,  -24,  -52,
+    -24,  -20,   10,    9,   -1,   -9,  -19,  -41,
+    -17,    3,   22,   22,   22,   11,    8,  -18,
+    -18,   -6,   16,   25,   16,   17,    4,  -18,
+    -23,   -3,   -1,   15,   10,   -3,  -20,  -22,
+    -42,  -20,  -10,   -5,   -2,  -20,  -23,  -44,
+    -29,  -51,  -23,  -15,  -22,  -18,  -50,  -64
 ];
 
 #[rustfmt::skip]
-pub const BISHOP_SCORE: [i32; 64] = [
-     0,   0,   0,   0,   0,   0,   0,   0,
-     0,   0,   0,   0,   0,   0,   0,   0,
-     0,   0,   0,  10,  10,   0,   0,   0,
-     0,   0,  10,  20,  20,  10,   0,   0,
-     0,   0,  10,  20,  20,  10,   0,   0,
-     0,  10,   0,   0,   0,   0,  10,   0,
-     0,  30,   0,   0,   0,   0,  30,   0,
-     0,   0, -10,   0,   0, -10,   0,   0
+pub const MG_BISHOP_SCORE: [i32; 64] = [
+    -29,    4,  -82,  -37,  -25,  -42,    7,   -8,
+    -26,   16,  -18,  -13,   30,   59,   18,  -47,
+    -16,   37,   43,   40,   35,   50,   37,   -2,
+     -4,    5,   19,   50,   37,   37,    7,   -2,
+     -6,   13,   13,   26,   34,   12,   10,    4,
+      0,   15,   15,   15,   14,   27,   18,   10,
+      4,   15,   16,    0,    7,   21,   33,    1,
+    -33,   -3,  -14,  -21,  -13,  -12,  -39,  -21
 ];
 
 #[rustfmt::skip]
-pub const ROOK_SCORE: [i32; 64] = [
-    50,  50,  50,  50,  50,  50,  50,  50,
-    50,  50,  50,  50,  50,  50,  50,  50,
-     0,   0,  10,  20,  20,  10,   0,   0,
-     0,   0,  10,  20,  20,  10,   0,   0,
-     0,   0,  10,  20,  20,  10,   0,   0,
-     0,   0,  10,  20,  20,  10,   0,   0,
-     0,   0,  10,  20,  20,  10,   0,   0,
-     0,   0,   0,  20,  20,   0,   0,   0
+pub const EG_BISHOP_SCORE: [i32; 64] = [
+    -14,  -21,  -11,   -8,   -7,   -9,  -17,  -24,
+     -8,   -4,    7,  -12,   -3,  -13,   -4,  -14,
+      2,   -8,    0,   -1,   -2,    6,    0,    4,
+     -3,    9,   12,    9,   14,   10,    3,    2,
+     -6,    3,   13,   19,    7,   10,   -3,   -9,
+    -12,   -3,    8,   10,   13,    3,   -7,  -15,
+    -14,  -18,   -7,   -1,    4,   -9,  -15,  -27,
+    -23,   -9,  -23,   -5,   -9,  -16,   -5,  -17
 ];
 
 #[rustfmt::skip]
-pub const KING_SCORE: [i32; 64] = [
-     0,   0,   0,   0,   0,   0,   0,   0,
-     0,   0,   5,   5,   5,   5,   0,   0,
-     0,   5,   5,  10,  10,   5,   5,   0,
-     0,   5,  10,  20,  20,  10,   5,   0,
-     0,   5,  10,  20,  20,  10,   5,   0,
-     0,   0,   5,  10,  10,   5,   0,   0,
-     0,   5,   5,  -5,  -5,   0,   5,   0,
-     0,   0,   5,   0, -15,   0,  10,   0
+pub const MG_ROOK_SCORE: [i32; 64] = [
+     32,   42,   32,   51,   63,    9,   31,   43,
+     27,   32,   58,   62,   80,   67,   26,   44,
+     -5,   19,   26,   36,   17,   45,   61,   16,
+    -24,  -11,    7,   26,   24,   35,   -8,  -20,
+    -36,  -26,  -12,   -1,    9,   -7,    6,  -23,
+    -45,  -25,  -16,  -17,    3,    0,   -5,  -33,
+    -44,  -16,  -20,   -9,   -1,   11,   -6,  -71,
+    -19,  -13,    1,   17,   16,    7,  -37,  -26
+];
+
+#[rustfmt::skip]
+pub const EG_ROOK_SCORE: [i32; 64] = [
+     13,   10,   18,   15,   12,   12,    8,    5,
+     11,   13,   13,   11,   -3,    3,    8,    3,
+      7,    7,    7,    5,    4,   -3,   -5,   -3,
+      4,    3,   13,    1,    2,    1,   -1,    2,
+      3,    5,    8,    4,   -5,   -6,   -8,  -11,
+     -4,    0,   -5,   -1,   -7,  -12,   -8,  -16,
+     -6,   -6,    0,    2,   -9,   -9,  -11,   -3,
+     -9,    2,    3,   -1,   -5,  -13,    4,  -20
+];
+
+#[rustfmt::skip]
+pub const MG_QUEEN_SCORE: [i32; 64] = [
+    -28,    0,   29,   12,   59,   44,   43,   45,
+    -24,  -39,   -5,    1,  -16,   57,   28,   54,
+    -13,  -17,    7,    8,   29,   56,   47,   57,
+    -27,  -27,  -16,  -16,   -1,   17,   -2,    1,
+     -9,  -26,   -9,  -10,   -2,   -4,    3,   -3,
+    -14,    2,  -11,   -2,   -5,    2,   14,    5,
+    -35,   -8,   11,    2,    8,   15,   -3,    1,
+     -1,  -18,   -9,   10,  -15,  -25,  -31,  -50
+];
+
+#[rustfmt::skip]
+pub const EG_QUEEN_SCORE: [i32; 64] = [
+     -9,   22,   22,   27,   27,   19,   10,   20,
+    -17,   20,   32,   41,   58,   25,   30,    0,
+    -20,    6,    9,   49,   47,   35,   19,    9,
+      3,   22,   24,   45,   57,   40,   57,   36,
+    -18,   28,   19,   47,   31,   34,   39,   23,
+    -16,  -27,   15,    6,    9,   17,   10,    5,
+    -22,  -23,  -30,  -16,  -16,  -23,  -36,  -32,
+    -33,  -28,  -22,  -43,   -5,  -32,  -20,  -41
+];
+
+#[rustfmt::skip]
+pub const MG_KING_SCORE: [i32; 64] = [
+    -65,   23,   16,  -15,  -56,  -34,    2,   13,
+     29,   -1,  -20,   -7,   -8,   -4,  -38,  -29,
+     -9,   24,    2,  -16,  -20,    6,   22,  -22,
+    -17,  -20,  -12,  -27,  -30,  -25,  -14,  -36,
+    -49,   -1,  -27,  -39,  -46,  -44,  -33,  -51,
+    -14,  -14,  -22,  -46,  -44,  -30,  -15,  -27,
+      1,    7,   -8,  -64,  -43,  -16,    9,    8,
+    -15,   36,   12,  -54,    8,  -28,   24,   14
+];
+
+#[rustfmt::skip]
+pub const EG_KING_SCORE: [i32; 64] = [
+    -74,  -35,  -18,  -18,  -11,   15,    4,  -17,
+    -12,   17,   14,   17,   17,   38,   23,   11,
+     10,   17,   23,   15,   20,   45,   44,   13,
+     -8,   22,   24,   27,   26,   33,   26,    3,
+    -18,   -4,   21,   24,   27,   23,    9,  -11,
+    -19,   -3,   11,   21,   23,   16,    7,   -9,
+    -27,  -11,    4,   13,   14,    4,   -5,  -17,
+    -53,  -34,  -21,  -11,  -28,  -14,  -24,  -43
+];
+
+/// Midgame piece-square tables indexed by piece type
+pub const MG_PST: [[i32; 64]; 6] = [
+    MG_PAWN_SCORE,
+    MG_KNIGHT_SCORE,
+    MG_BISHOP_SCORE,
+    MG_ROOK_SCORE,
+    MG_QUEEN_SCORE,
+    MG_KING_SCORE,
+];
+/// Endgame piece-square tables indexed by piece type
+pub const EG_PST: [[i32; 64]; 6] = [
+    EG_PAWN_SCORE,
+    EG_KNIGHT_SCORE,
+    EG_BISHOP_SCORE,
+    EG_ROOK_SCORE,
+    EG_QUEEN_SCORE,
+    EG_KING_SCORE,
 ];
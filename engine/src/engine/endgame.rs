@@ -0,0 +1,435 @@
+use super::Engine;
+use crate::calculated::kpk;
+use crate::calculated::values::*;
+use crate::repr::Board;
+use crate::repr::bitboards::*;
+use crate::repr::constants::*;
+
+/// Number of each piece type other than the king for the stronger side and then the weaker side,
+/// four bits to a count
+type Signature = u64;
+
+/// Signature of material written as the pieces of the stronger side and then the weaker side,
+/// each starting with their king, as in `KBNK`
+const fn signature(code: &str) -> Signature {
+    let code = code.as_bytes();
+    let mut signature = 0;
+    let mut kings = 0;
+    let mut i = 0;
+    while i < code.len() {
+        let piece = match code[i] {
+            b'K' => {
+                kings += 1;
+                i += 1;
+                continue;
+            }
+            b'P' => PAWN,
+            b'N' => KNIGHT,
+            b'B' => BISHOP,
+            b'R' => ROOK,
+            b'Q' => QUEEN,
+            _ => panic!("unknown piece in material signature"),
+        };
+        signature += 1 << (4 * ((kings - 1) * 5 + piece as usize));
+        i += 1;
+    }
+    signature
+}
+
+const KBNK: Signature = signature("KBNK");
+const KPK: Signature = signature("KPK");
+const KRKP: Signature = signature("KRKP");
+const KQKP: Signature = signature("KQKP");
+
+/// Signature of the material on the board with the given colour as the stronger side
+fn material_signature(board: &Board, strong: u8) -> Signature {
+    let mut signature = 0;
+    for (side, colour) in [strong, 1 - strong].into_iter().enumerate() {
+        for piece in PAWN..KING {
+            let count = board.pieces[colour as usize][piece as usize].count_ones() as u64;
+            signature |= count.min(15) << (4 * (side * 5 + piece as usize));
+        }
+    }
+    signature
+}
+
+impl Engine {
+    /// Score from White's point of view of an endgame with a specialised evaluator, which is
+    /// used in place of the general evaluation
+    pub(super) fn evaluate_endgame(&self) -> Option<i32> {
+        let kings = [
+            self.board.king_square(WHITE)?,
+            self.board.king_square(BLACK)?,
+        ];
+
+        for strong in [WHITE, BLACK] {
+            let (strong_king, weak_king) = (kings[strong as usize], kings[1 - strong as usize]);
+            let score = match material_signature(&self.board, strong) {
+                KBNK => self.kbnk(strong, strong_king, weak_king),
+                KPK => self.kpk(strong, strong_king, weak_king),
+                KRKP => self.krkp(strong, strong_king, weak_king),
+                KQKP => self.kqkp(strong, strong_king, weak_king),
+                // The weaker side has a bare king
+                signature if signature >> 20 == 0 && self.can_force_mate(strong) => {
+                    self.kxk(strong, strong_king, weak_king)
+                }
+                _ => continue,
+            };
+            return Some(if strong == WHITE { score } else { -score });
+        }
+
+        None
+    }
+
+    /// Whether a colour has the pieces to mate a bare king by force
+    fn can_force_mate(&self, colour: u8) -> bool {
+        let pieces = self.board.pieces[colour as usize];
+        let bishops = pieces[BISHOP as usize];
+
+        pieces[ROOK as usize] | pieces[QUEEN as usize] != 0
+            || (bishops & DARK_SQUARES != 0 && bishops & !DARK_SQUARES != 0)
+            || (bishops != 0 && pieces[KNIGHT as usize] != 0)
+    }
+
+    /// Endgame material of a colour
+    fn material(&self, colour: u8) -> i32 {
+        (PAWN..KING)
+            .map(|piece| {
+                self.eval_params.eg_piece_values[piece as usize]
+                    * self.board.pieces[colour as usize][piece as usize].count_ones() as i32
+            })
+            .sum()
+    }
+
+    /// Mating material against a bare king, driving it to the edge and bringing the kings together
+    fn kxk(&self, strong: u8, strong_king: u8, weak_king: u8) -> i32 {
+        let score = KNOWN_WIN
+            + self.material(strong)
+            + push_to_edge(weak_king)
+            + push_close(strong_king, weak_king);
+        // Stay clear of mate scores however much material is left
+        score.min(MATE_VALUE / 2)
+    }
+
+    /// Bishop and knight against a bare king, which can only be mated in a corner the bishop covers
+    fn kbnk(&self, strong: u8, strong_king: u8, weak_king: u8) -> i32 {
+        let dark = self.board.pieces[strong as usize][BISHOP as usize] & DARK_SQUARES != 0;
+        let corners = if dark { [A1, H8] } else { [A8, H1] };
+        let corner_distance = corners
+            .into_iter()
+            .map(|corner| distance(weak_king, corner))
+            .min()
+            .unwrap_or_default();
+
+        KNOWN_WIN
+            + self.material(strong)
+            + push_to_edge(weak_king)
+            + push_close(strong_king, weak_king)
+            + 30 * (7 - corner_distance)
+    }
+
+    /// King and pawn against king, looked up in the bitbase
+    fn kpk(&self, strong: u8, strong_king: u8, weak_king: u8) -> i32 {
+        let pawn = self.board.pieces[strong as usize][PAWN as usize].trailing_zeros() as u8;
+
+        // Look the position up with the stronger side as White and the pawn on the queen side
+        let normalise = |square: u8| {
+            let square = if strong == WHITE { square } else { square ^ 56 };
+            if pawn % 8 >= 4 { square ^ 7 } else { square }
+        };
+        let colour = if self.board.active_colour == strong {
+            WHITE
+        } else {
+            BLACK
+        };
+
+        if !kpk::probe(
+            colour,
+            normalise(strong_king),
+            normalise(pawn),
+            normalise(weak_king),
+        ) {
+            return 0;
+        }
+
+        KNOWN_WIN
+            + self.eval_params.eg_piece_values[PAWN as usize]
+            + relative_rank(strong, pawn) as i32
+    }
+
+    /// Rook against pawn, which is won unless the pawn is far advanced with its king beside it
+    fn krkp(&self, strong: u8, strong_king: u8, weak_king: u8) -> i32 {
+        // With the stronger side as White the pawn advances towards the first rank
+        let relative = |square: u8| if strong == WHITE { square } else { square ^ 56 };
+        let own = relative(strong_king);
+        let enemy = relative(weak_king);
+        let rook =
+            relative(self.board.pieces[strong as usize][ROOK as usize].trailing_zeros() as u8);
+        let pawn =
+            relative(self.board.pieces[1 - strong as usize][PAWN as usize].trailing_zeros() as u8);
+        let queening = pawn % 8;
+        let stop = pawn - 8;
+        let strong_to_move = i32::from(self.board.active_colour == strong);
+        let rook_value = self.eval_params.eg_piece_values[ROOK as usize];
+
+        if own % 8 == pawn % 8 && own < pawn {
+            // The king stands in front of the pawn
+            rook_value - distance(own, pawn)
+        } else if distance(enemy, pawn) >= 3 + (1 - strong_to_move) && distance(enemy, rook) >= 3 {
+            // The defending king is too far from both the pawn and the rook
+            rook_value - distance(own, pawn)
+        } else if enemy / 8 <= 2
+            && distance(enemy, pawn) == 1
+            && own / 8 >= 3
+            && distance(own, pawn) > 2 + strong_to_move
+        {
+            // The pawn is far advanced, supported by its king and out of reach of the other one
+            80 - 8 * distance(own, pawn)
+        } else {
+            200 - 8 * (distance(own, stop) - distance(enemy, stop) - distance(pawn, queening))
+        }
+    }
+
+    /// Queen against pawn, which is only drawn by a bishop or rook pawn on the seventh rank beside
+    /// its king
+    fn kqkp(&self, strong: u8, strong_king: u8, weak_king: u8) -> i32 {
+        let weak = 1 - strong;
+        let pawn = self.board.pieces[weak as usize][PAWN as usize].trailing_zeros() as u8;
+        let mut score = push_close(strong_king, weak_king);
+
+        if relative_rank(weak, pawn) != 6
+            || distance(weak_king, pawn) != 1
+            || matches!(pawn % 8, 1 | 3 | 4 | 6)
+        {
+            let values = self.eval_params.eg_piece_values;
+            score += values[QUEEN as usize] - values[PAWN as usize];
+        }
+
+        score
+    }
+
+    /// Share out of `SCALE_NORMAL` of the endgame score that the side it favours can expect to
+    /// realise given the material left
+    pub(super) fn scale_factor(&self, endgame: i32) -> i32 {
+        let strong = if endgame > 0 { WHITE } else { BLACK };
+        let weak = 1 - strong;
+        let own = self.board.pieces[strong as usize];
+        let enemy = self.board.pieces[weak as usize];
+        let non_pawn = |pieces: [u64; 6]| -> i32 {
+            (KNIGHT..KING)
+                .map(|piece| {
+                    PIECE_VALUES[piece as usize] * pieces[piece as usize].count_ones() as i32
+                })
+                .sum()
+        };
+        let (own_material, enemy_material) = (non_pawn(own), non_pawn(enemy));
+
+        // Without pawns at most a minor piece up, or with just knights, there is no mate to force
+        if own[PAWN as usize] == 0 {
+            if own_material - enemy_material <= BISHOP_VALUE {
+                return if own_material < ROOK_VALUE {
+                    0
+                } else if enemy_material <= BISHOP_VALUE {
+                    4
+                } else {
+                    14
+                };
+            }
+            let only_knights = own[BISHOP as usize] | own[ROOK as usize] | own[QUEEN as usize] == 0;
+            if only_knights && enemy[PAWN as usize] == 0 {
+                return 0;
+            }
+        }
+
+        if let Some(scale) = self.rook_pawns(strong) {
+            return scale;
+        }
+
+        let (own_bishops, enemy_bishops) = (own[BISHOP as usize], enemy[BISHOP as usize]);
+        if own_bishops.count_ones() == 1
+            && enemy_bishops.count_ones() == 1
+            && (own_bishops & DARK_SQUARES == 0) != (enemy_bishops & DARK_SQUARES == 0)
+        {
+            return if own_material == BISHOP_VALUE && enemy_material == BISHOP_VALUE {
+                SCALE_OPPOSITE_BISHOPS
+            } else {
+                SCALE_OPPOSITE_BISHOPS_WITH_PIECES
+            };
+        }
+
+        if own_material == ROOK_VALUE
+            && enemy_material == ROOK_VALUE
+            && own[ROOK as usize] != 0
+            && enemy[ROOK as usize] != 0
+        {
+            return self.rook_ending(strong);
+        }
+
+        SCALE_NORMAL
+    }
+
+    /// Scale factor for pawns all on one rook file, with at most a bishop that doesn't cover the
+    /// promotion square, against a king that has reached the corner in front of them
+    fn rook_pawns(&self, strong: u8) -> Option<i32> {
+        let weak = 1 - strong;
+        let own = self.board.pieces[strong as usize];
+        let enemy = self.board.pieces[weak as usize];
+        let pawns = own[PAWN as usize];
+        let bishops = own[BISHOP as usize];
+
+        if pawns == 0
+            || (own[KNIGHT as usize] | own[ROOK as usize] | own[QUEEN as usize]) != 0
+            || bishops.count_ones() > 1
+            || (KNIGHT..KING).any(|piece| enemy[piece as usize] != 0)
+        {
+            return None;
+        }
+
+        let file = if pawns & !A_FILE == 0 {
+            0
+        } else if pawns & !H_FILE == 0 {
+            7
+        } else {
+            return None;
+        };
+        let queening = if strong == WHITE { file + 56 } else { file };
+        if bishops & DARK_SQUARES != 0 && 1 << queening & DARK_SQUARES != 0
+            || bishops & !DARK_SQUARES != 0 && 1 << queening & !DARK_SQUARES != 0
+        {
+            return None;
+        }
+
+        let weak_king = self.board.king_square(weak)?;
+        let front = if strong == WHITE {
+            63 - pawns.leading_zeros() as u8
+        } else {
+            pawns.trailing_zeros() as u8
+        };
+        (distance(weak_king, queening) <= 1
+            && relative_rank(strong, weak_king) > relative_rank(strong, front))
+        .then_some(0)
+    }
+
+    /// Scale factor for rook endings, which are drawish when the defending king blockades a lone
+    /// pawn or the pawns are all on one side of the board
+    fn rook_ending(&self, strong: u8) -> i32 {
+        let weak = 1 - strong;
+        let own_pawns = self.board.pieces[strong as usize][PAWN as usize];
+        let enemy_pawns = self.board.pieces[weak as usize][PAWN as usize];
+
+        if own_pawns.count_ones() == 1
+            && enemy_pawns == 0
+            && let Some(weak_king) = self.board.king_square(weak)
+        {
+            let pawn = own_pawns.trailing_zeros() as u8;
+            if weak_king % 8 == pawn % 8
+                && relative_rank(strong, weak_king) > relative_rank(strong, pawn)
+            {
+                return SCALE_ROOK_PAWN_FORTRESS;
+            }
+        }
+
+        const QUEEN_SIDE: u64 = 0x0f0f0f0f0f0f0f0f;
+        let pawns = own_pawns | enemy_pawns;
+        if own_pawns.count_ones().abs_diff(enemy_pawns.count_ones()) <= 1
+            && (pawns & QUEEN_SIDE == 0 || pawns & !QUEEN_SIDE == 0)
+        {
+            return SCALE_ROOK_ONE_SIDE;
+        }
+
+        SCALE_NORMAL
+    }
+}
+
+/// Bonus for a king being near the edge of the board, and most of all in a corner
+fn push_to_edge(square: u8) -> i32 {
+    let edge_distance = |line: u8| line.min(7 - line) as i32;
+    let (file, rank) = (edge_distance(square % 8), edge_distance(square / 8));
+    90 - (7 * file * file / 2 + 7 * rank * rank / 2)
+}
+
+/// Bonus for two squares being close together
+fn push_close(a: u8, b: u8) -> i32 {
+    140 - 20 * distance(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endgame(fen: &str) -> Option<i32> {
+        let mut engine = Engine::new();
+        engine.board = Board::from_fen(fen).unwrap();
+        engine.evaluate_endgame()
+    }
+
+    fn scale(fen: &str) -> i32 {
+        let mut engine = Engine::new();
+        engine.board = Board::from_fen(fen).unwrap();
+        let [white, black] = engine.trace().totals();
+        engine.scale_factor(white.1 - black.1)
+    }
+
+    #[test]
+    fn signatures() {
+        let board = Board::from_fen("8/8/4k3/8/8/2B5/3NK3/8 w - - 0 1").unwrap();
+        assert_eq!(material_signature(&board, WHITE), KBNK);
+        assert_ne!(material_signature(&board, BLACK), KBNK);
+    }
+
+    #[test]
+    fn mating() {
+        // A king in the corner is closer to being mated than one in the centre
+        let centre = endgame("8/8/8/3k4/8/8/8/1R2K3 w - - 0 1").unwrap();
+        let corner = endgame("k7/8/8/8/8/8/8/1R2K3 w - - 0 1").unwrap();
+        assert!(corner > centre && centre > KNOWN_WIN);
+        assert_eq!(endgame("1r2k3/8/8/8/3K4/8/8/8 w - - 0 1"), Some(-centre));
+
+        // A dark squared bishop mates in the dark corners
+        let right = endgame("7k/8/5K2/8/8/8/8/B5N1 w - - 0 1").unwrap();
+        let wrong = endgame("k7/8/2K5/8/8/8/8/B5N1 w - - 0 1").unwrap();
+        assert!(right > wrong && wrong > KNOWN_WIN);
+    }
+
+    #[test]
+    fn pawn_endings() {
+        assert!(endgame("3k4/8/3K4/3P4/8/8/8/8 w - - 0 1").unwrap() > KNOWN_WIN);
+        assert_eq!(endgame("k7/8/1K6/P7/8/8/8/8 w - - 0 1"), Some(0));
+        // Mirrored to the king side and to Black
+        assert_eq!(endgame("7k/8/6K1/7P/8/8/8/8 w - - 0 1"), Some(0));
+        assert!(endgame("8/8/8/8/4p3/4k3/8/4K3 b - - 0 1").unwrap() < -KNOWN_WIN);
+    }
+
+    #[test]
+    fn pawn_against_pieces() {
+        let rook_wins = endgame("7k/8/8/8/3p4/8/1K6/R7 w - - 0 1").unwrap();
+        let rook_struggles = endgame("8/8/8/8/8/2K5/3pk3/R7 b - - 0 1").unwrap();
+        assert!(rook_wins > rook_struggles);
+
+        // A bishop pawn on the seventh beside its king holds against the queen
+        let centre_pawn = endgame("8/8/8/8/8/1Q6/3pk3/6K1 w - - 0 1").unwrap();
+        let bishop_pawn = endgame("8/8/8/8/8/1Q6/2pk4/6K1 w - - 0 1").unwrap();
+        assert!(centre_pawn > bishop_pawn + 500);
+    }
+
+    #[test]
+    fn scaling() {
+        assert_eq!(scale("8/8/4k3/8/8/2N5/8/4K3 w - - 0 1"), 0);
+        assert_eq!(scale("8/8/4k3/8/8/2NN4/8/4K3 w - - 0 1"), 0);
+        // Wrong rook pawn and bishop
+        assert_eq!(scale("k7/8/8/P7/8/8/1B6/4K3 w - - 0 1"), 0);
+        assert_eq!(scale("k7/8/8/P7/8/8/2B5/4K3 w - - 0 1"), SCALE_NORMAL);
+        assert_eq!(
+            scale("8/3b1k2/8/2p1p3/4P3/3PB3/5K2/8 w - - 0 1"),
+            SCALE_OPPOSITE_BISHOPS
+        );
+        assert_eq!(
+            scale("4k3/4r3/4P3/8/4K3/8/8/R7 w - - 0 1"),
+            SCALE_ROOK_PAWN_FORTRESS
+        );
+        assert_eq!(
+            scale("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+            SCALE_NORMAL
+        );
+    }
+}
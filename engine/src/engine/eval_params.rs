@@ -0,0 +1,295 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::calculated::values::*;
+
+#[derive(Error, Debug)]
+pub enum EvalParamsError {
+    #[error("failed to access evaluation parameters: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unknown evaluation parameter: {0}")]
+    Unknown(String),
+    #[error("evaluation parameter {name} should have {expected} values but has {actual}")]
+    Count {
+        name: String,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("invalid value for evaluation parameter {0}")]
+    Value(String),
+}
+
+/// A tunable evaluation weight made up of integers
+trait Param {
+    fn values(&self) -> Vec<i32>;
+    fn values_mut(&mut self) -> Vec<&mut i32>;
+    /// Rust expression for the weight, for writing it back out as a constant
+    fn literal(&self) -> String;
+}
+
+impl Param for i32 {
+    fn values(&self) -> Vec<i32> {
+        vec![*self]
+    }
+
+    fn values_mut(&mut self) -> Vec<&mut i32> {
+        vec![self]
+    }
+
+    fn literal(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Param for (i32, i32) {
+    fn values(&self) -> Vec<i32> {
+        vec![self.0, self.1]
+    }
+
+    fn values_mut(&mut self) -> Vec<&mut i32> {
+        vec![&mut self.0, &mut self.1]
+    }
+
+    fn literal(&self) -> String {
+        format!("({}, {})", self.0, self.1)
+    }
+}
+
+impl<T: Param, const N: usize> Param for [T; N] {
+    fn values(&self) -> Vec<i32> {
+        self.iter().flat_map(Param::values).collect()
+    }
+
+    fn values_mut(&mut self) -> Vec<&mut i32> {
+        self.iter_mut().flat_map(Param::values_mut).collect()
+    }
+
+    fn literal(&self) -> String {
+        let items: Vec<String> = self.iter().map(Param::literal).collect();
+        format!("[{}]", items.join(", "))
+    }
+}
+
+/// Declare the evaluation parameters along with the constant each one defaults to, so that the
+/// constant can be regenerated from tuned values
+macro_rules! eval_params {
+    ($($(#[$doc:meta])* $field:ident: $ty:ty = $constant:ident,)*) => {
+        /// Weights of the handcrafted evaluation, which default to the constants in
+        /// `calculated::values`
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct EvalParams {
+            $($(#[$doc])* pub $field: $ty,)*
+        }
+
+        impl Default for EvalParams {
+            fn default() -> Self {
+                Self {
+                    $($field: $constant,)*
+                }
+            }
+        }
+
+        impl EvalParams {
+            /// Name, type and weight of every parameter
+            fn params(&self) -> Vec<(&'static str, &'static str, &dyn Param)> {
+                vec![$((stringify!($constant), stringify!($ty), &self.$field as &dyn Param),)*]
+            }
+
+            fn params_mut(&mut self) -> Vec<(&'static str, &mut dyn Param)> {
+                vec![$((stringify!($constant), &mut self.$field as &mut dyn Param),)*]
+            }
+        }
+    };
+}
+
+eval_params! {
+    mg_piece_values: [i32; 6] = MG_PIECE_VALUES,
+    eg_piece_values: [i32; 6] = EG_PIECE_VALUES,
+    mg_pst: [[i32; 64]; 6] = MG_PST,
+    eg_pst: [[i32; 64]; 6] = EG_PST,
+    passed_pawn: [(i32, i32); 8] = PASSED_PAWN,
+    free_passed_pawn: [i32; 8] = FREE_PASSED_PAWN,
+    blocked_passed_pawn: (i32, i32) = BLOCKED_PASSED_PAWN,
+    passed_pawn_own_king_distance: i32 = PASSED_PAWN_OWN_KING_DISTANCE,
+    passed_pawn_enemy_king_distance: i32 = PASSED_PAWN_ENEMY_KING_DISTANCE,
+    doubled_pawn: (i32, i32) = DOUBLED_PAWN,
+    isolated_pawn: (i32, i32) = ISOLATED_PAWN,
+    backward_pawn: (i32, i32) = BACKWARD_PAWN,
+    phalanx_pawn: [(i32, i32); 8] = PHALANX_PAWN,
+    supported_pawn: (i32, i32) = SUPPORTED_PAWN,
+    mobility: [(i32, i32); 6] = MOBILITY,
+    bishop_pair: (i32, i32) = BISHOP_PAIR,
+    rook_open_file: (i32, i32) = ROOK_OPEN_FILE,
+    rook_semi_open_file: (i32, i32) = ROOK_SEMI_OPEN_FILE,
+    rook_on_seventh: (i32, i32) = ROOK_ON_SEVENTH,
+    knight_outpost: (i32, i32) = KNIGHT_OUTPOST,
+    trapped_rook: (i32, i32) = TRAPPED_ROOK,
+    trapped_bishop: (i32, i32) = TRAPPED_BISHOP,
+    pawn_shield: [i32; 8] = PAWN_SHIELD,
+    missing_shield_pawn: i32 = MISSING_SHIELD_PAWN,
+    pawn_storm: [i32; 8] = PAWN_STORM,
+    king_semi_open_file: i32 = KING_SEMI_OPEN_FILE,
+    king_open_file: i32 = KING_OPEN_FILE,
+    king_attack_weights: [i32; 6] = KING_ATTACK_WEIGHTS,
+    safe_check_weights: [i32; 6] = SAFE_CHECK_WEIGHTS,
+}
+
+impl EvalParams {
+    /// Every weight flattened in a fixed order
+    pub fn to_vec(&self) -> Vec<i32> {
+        self.params()
+            .into_iter()
+            .flat_map(|(_, _, param)| param.values())
+            .collect()
+    }
+
+    /// Set every weight from values flattened in the order of `to_vec`
+    pub fn set_from_slice(&mut self, values: &[i32]) {
+        let mut values = values.iter();
+        for (_, param) in self.params_mut() {
+            for value in param.values_mut() {
+                *value = *values.next().expect("one value for every weight");
+            }
+        }
+    }
+
+    /// Read parameters saved by `save`, where any parameter missing keeps its default
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, EvalParamsError> {
+        let mut params = Self::default();
+        for line in std::fs::read_to_string(path)?.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(name) = fields.next() else {
+                continue;
+            };
+            let values = fields
+                .map(|value| value.parse())
+                .collect::<Result<Vec<i32>, _>>()
+                .map_err(|_| EvalParamsError::Value(name.to_string()))?;
+
+            let mut params = params.params_mut();
+            let (_, param) = params
+                .iter_mut()
+                .find(|(constant, _)| *constant == name)
+                .ok_or_else(|| EvalParamsError::Unknown(name.to_string()))?;
+
+            let mut weights = param.values_mut();
+            if weights.len() != values.len() {
+                return Err(EvalParamsError::Count {
+                    name: name.to_string(),
+                    expected: weights.len(),
+                    actual: values.len(),
+                });
+            }
+            for (weight, value) in weights.iter_mut().zip(values) {
+                **weight = value;
+            }
+        }
+        Ok(params)
+    }
+
+    /// Write each parameter on its own line as its constant's name followed by its values
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), EvalParamsError> {
+        let lines: Vec<String> = self
+            .params()
+            .into_iter()
+            .map(|(name, _, param)| {
+                let values: Vec<String> = param.values().iter().map(i32::to_string).collect();
+                format!("{name} {}", values.join(" "))
+            })
+            .collect();
+        std::fs::write(path, lines.join("\n") + "\n")?;
+        Ok(())
+    }
+
+    /// Rust constant declarations for the parameters, to replace those in `calculated::values`
+    pub fn to_rust(&self) -> String {
+        self.params()
+            .into_iter()
+            .map(|(name, ty, param)| match name {
+                // The piece-square tables are declared a piece at a time, then gathered into
+                // `MG_PST` and `EG_PST`
+                "MG_PST" => (0..PST_PIECES.len())
+                    .flat_map(|piece| {
+                        [
+                            pst_table("MG", piece, &self.mg_pst[piece]),
+                            pst_table("EG", piece, &self.eg_pst[piece]),
+                        ]
+                    })
+                    .collect(),
+                "EG_PST" => String::new(),
+                _ => format!("pub const {name}: {ty} = {};\n", param.literal()),
+            })
+            .collect()
+    }
+}
+
+/// Names of the pieces in the per-piece piece-square tables, indexed by piece type
+const PST_PIECES: [&str; 6] = ["PAWN", "KNIGHT", "BISHOP", "ROOK", "QUEEN", "KING"];
+
+/// Declaration of one piece's piece-square table laid out as a board, as in `calculated::values`
+fn pst_table(stage: &str, piece: usize, table: &[i32; 64]) -> String {
+    let rows: Vec<String> = table
+        .chunks(8)
+        .map(|row| {
+            let values: Vec<String> = row.iter().map(|value| format!("{value:>4}")).collect();
+            format!("   {}", values.join(", "))
+        })
+        .collect();
+    format!(
+        "#[rustfmt::skip]\npub const {stage}_{}_SCORE: [i32; 64] = [\n{}\n];\n",
+        PST_PIECES[piece],
+        rows.join(",\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattening() {
+        let mut params = EvalParams::default();
+        let mut values = params.to_vec();
+        assert_eq!(values.len(), 2 * 6 + 2 * 6 * 64 + 109);
+
+        values[0] += 1;
+        params.set_from_slice(&values);
+        assert_eq!(params.mg_piece_values[0], MG_PIECE_VALUES[0] + 1);
+        assert_eq!(params.to_vec(), values);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut params = EvalParams {
+            bishop_pair: (1, 2),
+            ..Default::default()
+        };
+        params.eg_pst[5][63] = -7;
+
+        let path = std::env::temp_dir().join("chust_eval_params_test.txt");
+        params.save(&path).unwrap();
+        let loaded = EvalParams::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, params);
+    }
+
+    #[test]
+    fn rust_constants() {
+        let rust = EvalParams::default().to_rust();
+        assert!(rust.contains("pub const BISHOP_PAIR: (i32, i32) = (30, 50);\n"));
+        assert!(
+            rust.contains("pub const MG_PIECE_VALUES: [i32; 6] = [82, 337, 365, 477, 1025, 0];\n")
+        );
+
+        // The piece-square tables come out as written in `calculated::values`
+        let values = include_str!("../calculated/values.rs");
+        let start = values
+            .find("#[rustfmt::skip]\npub const MG_KNIGHT_SCORE")
+            .unwrap();
+        let end = start + values[start..].find("];\n").unwrap() + 3;
+        assert!(rust.contains(&values[start..end]));
+        assert!(!rust.contains("MG_PST"));
+        assert!(!rust.contains("EG_PST"));
+    }
+}
@@ -1,53 +1,138 @@
 use super::Engine;
+use super::trace::Term;
 use crate::calculated::values::*;
 use crate::repr::constants::*;
 
+/// Midgame and endgame scores of an evaluation term for each colour
+pub type TermScores = [(i32, i32); 2];
+
 impl Engine {
-    pub fn evaluate(&self) -> i32 {
-        let friend = self.board.pieces[self.board.active_colour as usize];
-        let enemy = self.board.pieces[1 - self.board.active_colour as usize];
-
-        let queens =
-            friend[QUEEN as usize].count_ones() as i8 - enemy[QUEEN as usize].count_ones() as i8;
-        let rooks =
-            friend[ROOK as usize].count_ones() as i8 - enemy[ROOK as usize].count_ones() as i8;
-        let bishops =
-            friend[BISHOP as usize].count_ones() as i8 - enemy[BISHOP as usize].count_ones() as i8;
-        let knights =
-            friend[KNIGHT as usize].count_ones() as i8 - enemy[KNIGHT as usize].count_ones() as i8;
-        let pawns =
-            friend[PAWN as usize].count_ones() as i8 - enemy[PAWN as usize].count_ones() as i8;
-
-        let mut score = QUEEN_VALUE * queens as i32
-            + ROOK_VALUE * rooks as i32
-            + BISHOP_VALUE * bishops as i32
-            + KNIGHT_VALUE * knights as i32
-            + PAWN_VALUE * pawns as i32;
+    /// Static evaluation relative to the side to move
+    pub fn evaluate(&mut self) -> i32 {
+        // Known endgames are evaluated precisely whichever evaluation is in use
+        let score = match self.evaluate_endgame() {
+            Some(score) => score,
+            None => match self.board.evaluate_nnue() {
+                Some(score) => self.scale_nnue(score),
+                None => self.evaluate_white(),
+            },
+        };
+        if self.board.active_colour == WHITE {
+            score
+        } else {
+            -score
+        }
+    }
+
+    /// Static evaluation from White's point of view
+    fn evaluate_white(&mut self) -> i32 {
+        let (midgame, endgame) = self
+            .evaluate_terms()
+            .iter()
+            .fold((0, 0), |total, [white, black]| {
+                add(total, (white.0 - black.0, white.1 - black.1))
+            });
+
+        let endgame = endgame * self.scale_factor(endgame) / SCALE_NORMAL;
+        taper(midgame, endgame, self.board.phase())
+    }
+
+    /// Scale a network evaluation relative to the side to move by the chances of realising it,
+    /// returning it from White's point of view
+    pub(super) fn scale_nnue(&self, score: i32) -> i32 {
+        let white = if self.board.active_colour == WHITE {
+            score
+        } else {
+            -score
+        };
+        white * self.scale_factor(white) / SCALE_NORMAL
+    }
+
+    /// Scores of every evaluation term, in the order of `Term::ALL`
+    pub(super) fn evaluate_terms(&mut self) -> [TermScores; Term::ALL.len()] {
+        let params = &self.eval_params;
+        let mut material = [(0, 0); 2];
+        let mut piece_squares = [(0, 0); 2];
 
         for (square, piece) in self.board.squares.iter().enumerate() {
             if let &Some((colour, piece)) = piece {
-                if colour == WHITE {
-                    match piece {
-                        PAWN => score += PAWN_SCORE[square],
-                        KNIGHT => score += KNIGHT_SCORE[square],
-                        BISHOP => score += BISHOP_SCORE[square],
-                        ROOK => score += ROOK_SCORE[square],
-                        KING => score += KING_SCORE[square],
-                        _ => {}
-                    };
-                } else {
-                    match piece {
-                        PAWN => score -= PAWN_SCORE[63 - square],
-                        KNIGHT => score -= KNIGHT_SCORE[63 - square],
-                        BISHOP => score -= BISHOP_SCORE[63 - square],
-                        ROOK => score -= ROOK_SCORE[63 - square],
-                        KING => score -= KING_SCORE[63 - square],
-                        _ => {}
-                    };
-                }
+                let piece = piece as usize;
+                let square = if colour == WHITE { square ^ 56 } else { square };
+                let colour = colour as usize;
+
+                material[colour] = add(
+                    material[colour],
+                    (params.mg_piece_values[piece], params.eg_piece_values[piece]),
+                );
+                piece_squares[colour] = add(
+                    piece_squares[colour],
+                    (params.mg_pst[piece][square], params.eg_pst[piece][square]),
+                );
             }
         }
 
-        score
+        [
+            material,
+            piece_squares,
+            self.evaluate_pawns(),
+            self.evaluate_pieces(),
+            self.evaluate_king_safety(),
+        ]
+    }
+}
+
+/// Interpolate between midgame and endgame scores by the game phase
+pub(super) fn taper(midgame: i32, endgame: i32, phase: i32) -> i32 {
+    // Integer division rounds towards zero, which keeps the taper symmetric between colours
+    (midgame * phase + endgame * (TOTAL_PHASE - phase)) / TOTAL_PHASE
+}
+
+/// Sum of two (midgame, endgame) score pairs
+pub(super) fn add(a: (i32, i32), b: (i32, i32)) -> (i32, i32) {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nnue::Network;
+    use crate::repr::Board;
+
+    const POSITIONS: [&str; 8] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "rn3r1k/p3qp2/bp2p2p/3pP3/P2NRQ2/1Pb2NPP/5PB1/2R3K1 w - - 1 22",
+        "8/8/4k3/8/2p5/8/B2K4/8 b - - 0 1",
+    ];
+
+    #[test]
+    fn symmetry() {
+        let mut engine = Engine::new();
+        for fen in POSITIONS {
+            engine.board = Board::from_fen(fen).unwrap();
+            let eval = engine.evaluate();
+            engine.board = engine.board.mirror();
+            assert_eq!(engine.evaluate(), eval, "{fen}");
+        }
+    }
+
+    #[test]
+    fn nnue_scaling() {
+        let mut engine = Engine::new();
+        engine.set_network(Some(Network::random(0)));
+        engine.set_use_nnue(true);
+
+        // A rook pawn with the wrong coloured bishop is a draw whatever the network thinks
+        engine.reset(Some("k7/8/8/P7/8/8/1B6/4K3 w - - 0 1".into()));
+        assert!(engine.board.evaluate_nnue().is_some_and(|score| score != 0));
+        assert_eq!(engine.evaluate(), 0);
+
+        // Positions with every piece left aren't scaled at all
+        engine.reset(None);
+        assert_eq!(Some(engine.evaluate()), engine.board.evaluate_nnue());
     }
 }
@@ -0,0 +1,164 @@
+use super::Engine;
+use super::evaluate::TermScores;
+use crate::calculated::values::*;
+use crate::calculated::{
+    KING_MOVES, generate_bishop_moves, generate_knight_moves, generate_rook_moves,
+};
+use crate::repr::bitboards::*;
+use crate::repr::constants::*;
+
+impl Engine {
+    /// Midgame and endgame king safety scores of each colour
+    pub(super) fn evaluate_king_safety(&self) -> TermScores {
+        // Attacks on the king stop mattering as material comes off, which the taper accounts for
+        [(self.king_safety(WHITE), 0), (self.king_safety(BLACK), 0)]
+    }
+
+    /// Midgame safety of a colour's king, negative when it is in danger
+    fn king_safety(&self, colour: u8) -> i32 {
+        let Some(king) = self.board.king_square(colour) else {
+            return 0;
+        };
+
+        self.pawn_shelter(colour, king) + self.king_attacks(colour, king)
+    }
+
+    /// Shield and storm pawns and open files on the king's file and those either side of it
+    fn pawn_shelter(&self, colour: u8, king: u8) -> i32 {
+        let own_pawns = self.board.pieces[colour as usize][PAWN as usize];
+        let enemy_pawns = self.board.pieces[1 - colour as usize][PAWN as usize];
+        let front = forward_ranks(colour, king);
+        let params = &self.eval_params;
+        let king_rank = relative_rank(colour, king);
+        let mut score = 0;
+
+        let king_file = king % 8;
+        for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+            let file = file_mask(file);
+
+            let shield = file & front & own_pawns;
+            match nearest(colour, shield) {
+                Some(pawn) => score += params.pawn_shield[relative_rank(colour, pawn) - king_rank],
+                None => score += params.missing_shield_pawn,
+            }
+
+            if let Some(pawn) = nearest(colour, file & front & enemy_pawns) {
+                score += params.pawn_storm[relative_rank(colour, pawn) - king_rank];
+            }
+
+            if file & own_pawns == 0 {
+                score += if file & enemy_pawns == 0 {
+                    params.king_open_file
+                } else {
+                    params.king_semi_open_file
+                };
+            }
+        }
+
+        score
+    }
+
+    /// Penalty from the enemy pieces attacking the squares around the king and able to check it
+    fn king_attacks(&self, colour: u8, king: u8) -> i32 {
+        let enemy = 1 - colour;
+        let blockers = self.board.blockers();
+        let zone = KING_MOVES[king as usize] | 1 << king;
+
+        // Squares an enemy piece could check from without being taken by a pawn
+        let safe = !self.board.colour_pieces(enemy)
+            & !pawn_attacks(colour, self.board.pieces[colour as usize][PAWN as usize]);
+        let diagonal_checks = generate_bishop_moves(king, blockers) & safe;
+        let orthogonal_checks = generate_rook_moves(king, blockers) & safe;
+        let knight_checks = generate_knight_moves(king) & safe;
+
+        let mut attackers = 0;
+        let mut units = 0;
+
+        for piece in KNIGHT..=QUEEN {
+            let mut pieces = self.board.pieces[enemy as usize][piece as usize];
+            while pieces != 0 {
+                let square = pieces.trailing_zeros() as u8;
+                pieces &= pieces - 1;
+
+                let (attacks, checks) = match piece {
+                    KNIGHT => (generate_knight_moves(square), knight_checks),
+                    BISHOP => (generate_bishop_moves(square, blockers), diagonal_checks),
+                    ROOK => (generate_rook_moves(square, blockers), orthogonal_checks),
+                    _ => (
+                        generate_bishop_moves(square, blockers)
+                            | generate_rook_moves(square, blockers),
+                        diagonal_checks | orthogonal_checks,
+                    ),
+                };
+
+                if attacks & zone != 0 {
+                    attackers += 1;
+                    units += self.eval_params.king_attack_weights[piece as usize]
+                        * (attacks & zone).count_ones() as i32;
+                }
+                if attacks & checks != 0 {
+                    units += self.eval_params.safe_check_weights[piece as usize];
+                }
+            }
+        }
+
+        // A lone attacker can rarely get through on its own
+        if attackers < 2 {
+            return 0;
+        }
+
+        -(units * units / KING_DANGER_DIVISOR).min(MAX_KING_DANGER)
+    }
+}
+
+/// Square of the piece in a set closest to the given colour's side of the board
+fn nearest(colour: u8, pieces: u64) -> Option<u8> {
+    match pieces {
+        0 => None,
+        _ if colour == WHITE => Some(pieces.trailing_zeros() as u8),
+        _ => Some(63 - pieces.leading_zeros() as u8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::Board;
+
+    fn safety(fen: &str, colour: u8) -> i32 {
+        let mut engine = Engine::new();
+        engine.board = Board::from_fen(fen).unwrap();
+        engine.king_safety(colour)
+    }
+
+    #[test]
+    fn shelter() {
+        let sheltered = safety("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1", WHITE);
+        let advanced = safety("4k3/8/8/8/8/6P1/5P1P/6K1 w - - 0 1", WHITE);
+        let open = safety("4k3/8/8/8/8/8/5P1P/6K1 w - - 0 1", WHITE);
+        assert!(sheltered > advanced);
+        assert!(advanced > open);
+
+        let stormed = safety("4k3/8/8/8/8/6p1/5PPP/6K1 w - - 0 1", WHITE);
+        let quiet = safety("4k3/6p1/8/8/8/8/5PPP/6K1 w - - 0 1", WHITE);
+        assert!(quiet > stormed);
+    }
+
+    #[test]
+    fn attacks() {
+        let attacked = safety("4k3/8/8/8/8/5n2/5PPq/6K1 w - - 0 1", WHITE);
+        let alone = safety("4k3/8/8/8/8/8/5PPq/6K1 w - - 0 1", WHITE);
+        assert!(alone > attacked);
+    }
+
+    #[test]
+    fn symmetry() {
+        let mut engine = Engine::new();
+        engine.board =
+            Board::from_fen("r1b2rk1/pp3ppp/2n1pq2/3p4/3P1P2/2PBPN2/P5PP/R2QK2R w KQ - 0 11")
+                .unwrap();
+        let [white, black] = engine.evaluate_king_safety();
+        engine.board = engine.board.mirror();
+        assert_eq!(engine.evaluate_king_safety(), [black, white]);
+    }
+}
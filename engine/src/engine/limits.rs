@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use crate::repr::Move;
+
+/// Moves assumed to be left in the game when the GUI doesn't say how many moves the time is for
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// Share of the increment spent on each move, in quarters
+const INCREMENT_QUARTERS: u32 = 3;
+
+/// Conditions that end a search, which stops at whichever is reached first
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Deepest iteration to search, otherwise the search deepens as far as it can
+    pub depth: Option<usize>,
+    /// Nodes after which the search stops, checked at every node so that fixed node searches are
+    /// reproducible
+    pub nodes: Option<usize>,
+    /// Time after which the search stops, once the first iteration has completed
+    pub movetime: Option<Duration>,
+    /// Stop as soon as a mate in at most this many moves has been found
+    pub mate: Option<usize>,
+    /// Moves the root is restricted to, or every move when empty
+    pub searchmoves: Vec<Move>,
+    /// Keep searching until stopped, holding back the best move if another limit is reached
+    /// first
+    pub infinite: bool,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: usize) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    pub fn nodes(nodes: usize) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..Self::default()
+        }
+    }
+}
+
+/// Time to spend on a move given the time left on the clock, the increment and, if the time
+/// control has one, the number of moves until the next time control, keeping the overhead back
+pub fn allocate_time(
+    time: Duration,
+    increment: Duration,
+    moves_to_go: Option<u32>,
+    overhead: Duration,
+) -> Duration {
+    let available = time.saturating_sub(overhead);
+    let moves = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+    (available / moves + increment * INCREMENT_QUARTERS / 4).min(available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_allocation() {
+        let ms = Duration::from_millis;
+        assert_eq!(allocate_time(ms(30_010), ms(0), None, ms(10)), ms(1_000));
+        assert_eq!(allocate_time(ms(30_010), ms(400), None, ms(10)), ms(1_300));
+        assert_eq!(allocate_time(ms(10_000), ms(0), Some(1), ms(0)), ms(10_000));
+        // Never more than is left on the clock
+        assert_eq!(allocate_time(ms(100), ms(2_000), None, ms(10)), ms(90));
+        assert_eq!(allocate_time(ms(5), ms(0), None, ms(10)), ms(0));
+    }
+}
@@ -1,23 +1,82 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::nnue::Network;
 use crate::repr::Board;
 use crate::repr::Move;
+pub use eval_params::{EvalParams, EvalParamsError};
+pub use limits::{SearchLimits, allocate_time};
+pub use options::{EngineOptions, Protocol};
+pub use params::{SearchParams, Tunable};
+use pawns::PawnTable;
+pub use registry::{GuiOption, OptionType, OptionValue, registry};
+pub use thread::{SearchSignals, SearchThread};
+pub use trace::{EvalTrace, Term};
+pub use transposition_table::DEFAULT_HASH_MB;
 use transposition_table::TranspositionTable;
+pub use wdl::wdl_material;
 
+mod endgame;
+mod eval_params;
 mod evaluate;
+mod king_safety;
+mod limits;
+mod options;
+mod ordering;
+mod params;
+mod pawns;
+mod pieces;
 mod print;
+mod registry;
 mod search;
+mod thread;
+mod trace;
 mod transposition_table;
+mod wdl;
 
 pub const MAX_PLY: usize = 64;
 
+/// History of a quiet move indexed by the piece and destination of the move before it, and then
+/// by its own piece and destination, with pieces indexed by colour and type
+pub type ContinuationHistory = [[[[i32; 64]; 12]; 64]; 12];
+
 pub struct Engine {
     pub board: Board,
+    pub params: SearchParams,
+    /// Weights of the handcrafted evaluation
+    pub eval_params: EvalParams,
+    pub options: EngineOptions,
+    /// Network loaded for evaluation, only used in place of the handcrafted evaluation when
+    /// `use_nnue` is set
+    pub network: Option<Arc<Network>>,
+    pub use_nnue: bool,
+    /// Suppress search output, for searches run by tools rather than a GUI
+    pub silent: bool,
     pub tt: TranspositionTable,
+    pub pawn_table: PawnTable,
     pub nodes: usize,
     pub ply: usize,
+    /// Depth of the current iterative deepening iteration
+    pub root_depth: usize,
+    /// Deepest ply reached in the current iteration, including quiescence
+    pub seldepth: usize,
+    /// Conditions that end the current search
+    pub limits: SearchLimits,
+    /// When the current search started
+    pub search_start: Instant,
+    /// Whether the current search reached one of its limits and is unwinding
+    pub stopped: bool,
+    /// Flags for stopping the search from another thread
+    pub signals: Arc<SearchSignals>,
     pub killer_moves: ([Option<Move>; MAX_PLY], [Option<Move>; MAX_PLY]),
     pub history_moves: [[i32; 64]; 12],
+    /// Quiet move that refuted each previous move, indexed by its piece and destination
+    pub counter_moves: [[Option<Move>; 64]; 12],
+    pub continuation_history: Box<ContinuationHistory>,
     pub pv_length: [usize; MAX_PLY],
     pub pv_table: [[Option<Move>; MAX_PLY]; MAX_PLY],
+    /// Move skipped at each ply while verifying a singular extension
+    pub excluded_moves: [Option<Move>; MAX_PLY],
 }
 
 impl Default for Engine {
@@ -30,27 +89,87 @@ impl Engine {
     pub fn new() -> Self {
         Self {
             board: Board::default(),
-            tt: TranspositionTable::new(),
+            params: SearchParams::default(),
+            eval_params: EvalParams::default(),
+            options: EngineOptions::default(),
+            network: None,
+            use_nnue: false,
+            silent: false,
+            tt: TranspositionTable::default(),
+            pawn_table: PawnTable::default(),
             nodes: 0,
             ply: 0,
+            root_depth: 0,
+            seldepth: 0,
+            limits: SearchLimits::default(),
+            search_start: Instant::now(),
+            stopped: false,
+            signals: Arc::default(),
             killer_moves: ([None; MAX_PLY], [None; MAX_PLY]),
             history_moves: [[0; 64]; 12],
+            counter_moves: [[None; 64]; 12],
+            continuation_history: vec![[[[0; 64]; 12]; 64]; 12]
+                .into_boxed_slice()
+                .try_into()
+                .expect("continuation history has one table per piece"),
             pv_length: [0; MAX_PLY],
             pv_table: [[None; MAX_PLY]; MAX_PLY],
+            excluded_moves: [None; MAX_PLY],
         }
     }
 
+    /// Set up a new position, keeping the transposition table as it is likely from the same game
     pub fn reset(&mut self, fen: Option<String>) {
         self.board = Board::from_fen(
             &fen.unwrap_or("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()),
         )
         .unwrap();
-        self.tt = TranspositionTable::new();
+        self.attach_network();
         self.nodes = 0;
         self.ply = 0;
+        self.root_depth = 0;
+        self.seldepth = 0;
         self.killer_moves = ([None; MAX_PLY], [None; MAX_PLY]);
         self.history_moves = [[0; 64]; 12];
+        self.counter_moves = [[None; 64]; 12];
+        self.continuation_history.fill([[[0; 64]; 12]; 64]);
         self.pv_length = [0; MAX_PLY];
         self.pv_table = [[None; MAX_PLY]; MAX_PLY];
+        self.excluded_moves = [None; MAX_PLY];
+    }
+
+    /// Discard everything learnt from previous searches
+    pub fn clear_hash(&mut self) {
+        self.tt.clear();
+        self.pawn_table.clear();
+    }
+
+    /// Load a network to evaluate with whenever NNUE is enabled, or unload it with `None` to
+    /// fall back to the handcrafted evaluation
+    pub fn set_network(&mut self, network: Option<Network>) {
+        self.network = network.map(Arc::new);
+        self.attach_network();
+    }
+
+    /// Switch between the network and the handcrafted evaluation
+    pub fn set_use_nnue(&mut self, use_nnue: bool) {
+        self.use_nnue = use_nnue;
+        self.attach_network();
+    }
+
+    fn attach_network(&mut self) {
+        let network = self.network.clone().filter(|_| self.use_nnue);
+        self.board.set_network(network);
+    }
+
+    /// Evaluate with different weights, dropping pawn structures scored with the old ones
+    pub fn set_eval_params(&mut self, eval_params: EvalParams) {
+        self.eval_params = eval_params;
+        self.pawn_table.clear();
+    }
+
+    /// Reallocate the transposition table with the given size in megabytes
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.tt = TranspositionTable::new(megabytes);
     }
 }
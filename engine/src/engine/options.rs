@@ -0,0 +1,37 @@
+/// Protocol the search reports its progress and result in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Protocol {
+    #[default]
+    Uci,
+    /// CECP, where the frontend sends the move itself so that it can decide whether to play it
+    Xboard,
+}
+
+/// Settings a GUI can configure that aren't part of the search or evaluation themselves
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EngineOptions {
+    /// Search threads requested, the search itself is single threaded
+    pub threads: usize,
+    /// Number of principal variations requested
+    pub multi_pv: usize,
+    /// Milliseconds kept back from each move's time to cover communication delays
+    pub move_overhead: u64,
+    /// Whether the GUI may send `go ponder`
+    pub ponder: bool,
+    /// Whether scores are reported with win, draw and loss chances
+    pub show_wdl: bool,
+    pub protocol: Protocol,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            multi_pv: 1,
+            move_overhead: 10,
+            ponder: false,
+            show_wdl: false,
+            protocol: Protocol::Uci,
+        }
+    }
+}
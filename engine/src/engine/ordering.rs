@@ -0,0 +1,143 @@
+use super::Engine;
+use crate::calculated::values::*;
+use crate::repr::Move;
+
+impl Engine {
+    pub(super) fn score_move(&mut self, mv: &Move, tt_move: Option<Move>) -> i32 {
+        if tt_move == Some(*mv) {
+            return TT_MOVE_SCORE;
+        }
+
+        if self.pv_table[0][self.ply] == Some(*mv) {
+            return PV_MOVE_SCORE;
+        }
+
+        let (_, piece) = self.board.squares[mv.source.0 as usize]
+            .expect("valid moves always have a piece at source");
+
+        if let Some(victim) = self.board.captured_piece(mv) {
+            // Captures, with those losing material ordered after every quiet move
+            let mvv_lva = MVV_LVA[piece as usize][victim as usize];
+            if self.board.see(mv) >= 0 {
+                GOOD_CAPTURE_SCORE + mvv_lva
+            } else {
+                BAD_CAPTURE_SCORE + mvv_lva
+            }
+        } else if self.killer_moves.0[self.ply] == Some(*mv) {
+            FIRST_KILLER_SCORE
+        } else if self.killer_moves.1[self.ply] == Some(*mv) {
+            SECOND_KILLER_SCORE
+        } else if self.counter_move() == Some(*mv) {
+            COUNTER_MOVE_SCORE
+        } else {
+            self.quiet_history(mv)
+        }
+    }
+
+    /// Quiet move that last refuted the opponent's previous move
+    fn counter_move(&self) -> Option<Move> {
+        let (piece, square) = self.previous_move(1)?;
+        self.counter_moves[piece][square]
+    }
+
+    /// Combined butterfly and continuation history of a quiet move
+    fn quiet_history(&self, mv: &Move) -> i32 {
+        let (piece, square) = self.moved_piece(mv);
+
+        let mut score = self.history_moves[piece][square];
+        for plies_ago in 1..=2 {
+            if let Some((previous_piece, previous_square)) = self.previous_move(plies_ago) {
+                score += self.continuation_history[previous_piece][previous_square][piece][square];
+            }
+        }
+        score
+    }
+
+    /// Reward the quiet move that caused a beta cutoff and penalise the quiet moves searched
+    /// before it, which failed to
+    pub(super) fn update_quiet_histories(&mut self, best: Move, failed: &[Move], depth: usize) {
+        self.killer_moves.1[self.ply] = self.killer_moves.0[self.ply];
+        self.killer_moves.0[self.ply] = Some(best);
+
+        if let Some((piece, square)) = self.previous_move(1) {
+            self.counter_moves[piece][square] = Some(best);
+        }
+
+        let bonus = (16 * depth * depth).min(MAX_HISTORY as usize / 8) as i32;
+        self.update_history(&best, bonus);
+        for mv in failed {
+            self.update_history(mv, -bonus);
+        }
+    }
+
+    fn update_history(&mut self, mv: &Move, bonus: i32) {
+        let (piece, square) = self.moved_piece(mv);
+
+        apply_gravity(&mut self.history_moves[piece][square], bonus);
+        for plies_ago in 1..=2 {
+            if let Some((previous_piece, previous_square)) = self.previous_move(plies_ago) {
+                apply_gravity(
+                    &mut self.continuation_history[previous_piece][previous_square][piece][square],
+                    bonus,
+                );
+            }
+        }
+    }
+
+    /// History index of the piece making a move and its destination
+    fn moved_piece(&self, mv: &Move) -> (usize, usize) {
+        let (colour, piece) = self.board.squares[mv.source.0 as usize]
+            .expect("valid moves always have a piece at source");
+        (
+            colour as usize * 6 + piece as usize,
+            mv.destination.0 as usize,
+        )
+    }
+
+    /// History index of the piece that moved the given number of plies ago and its destination
+    fn previous_move(&self, plies_ago: usize) -> Option<(usize, usize)> {
+        let previous = self.board.previous_move(plies_ago)?;
+        let (colour, piece) = previous.moved;
+        Some((
+            colour as usize * 6 + piece as usize,
+            previous.destination.0 as usize,
+        ))
+    }
+}
+
+/// Move a history entry towards the bonus, more slowly the closer it already is to the bound so
+/// that entries stay within `MAX_HISTORY` and old results decay
+fn apply_gravity(entry: &mut i32, bonus: i32) {
+    *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::MAX_PLY;
+
+    #[test]
+    fn refutations_before_quiet_moves() {
+        let mut engine = Engine::new();
+        engine.board.make_move(&Move::coordinate("e2e4"));
+
+        // Knight to c6 refuted e4 after a6 failed to
+        let (best, failed, quiet) = (
+            Move::coordinate("b8c6"),
+            Move::coordinate("a7a6"),
+            Move::coordinate("h7h6"),
+        );
+        engine.update_quiet_histories(best, &[failed], 4);
+        engine.killer_moves = ([None; MAX_PLY], [None; MAX_PLY]);
+
+        assert_eq!(engine.score_move(&best, None), COUNTER_MOVE_SCORE);
+        assert!(engine.score_move(&best, None) > engine.score_move(&quiet, None));
+
+        // Without the counter move or butterfly history, continuation history alone still orders
+        // it ahead
+        engine.counter_moves = [[None; 64]; 12];
+        engine.history_moves = [[0; 64]; 12];
+        assert!(engine.score_move(&best, None) > engine.score_move(&quiet, None));
+        assert!(engine.score_move(&quiet, None) > engine.score_move(&failed, None));
+    }
+}
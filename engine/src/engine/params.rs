@@ -0,0 +1,102 @@
+/// A numeric search parameter along with the range it can be tuned over
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tunable {
+    pub name: &'static str,
+    pub default: i32,
+    pub min: i32,
+    pub max: i32,
+}
+
+/// Declare the tunable search parameters with their defaults and ranges, so that tools can find
+/// and set them by name
+macro_rules! search_params {
+    ($($(#[$doc:meta])* $field:ident: $ty:ty = $default:literal, $min:literal..=$max:literal;)*) => {
+        /// Tunable margins, depth limits and toggles for the pruning done during search
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct SearchParams {
+            $($(#[$doc])* pub $field: $ty,)*
+            /// Whether quiet checks are searched at the first ply of quiescence
+            pub quiescence_checks: bool,
+        }
+
+        impl Default for SearchParams {
+            fn default() -> Self {
+                Self {
+                    $($field: $default,)*
+                    quiescence_checks: false,
+                }
+            }
+        }
+
+        impl SearchParams {
+            /// Every numeric parameter, named after its field
+            pub const TUNABLE: &[Tunable] = &[$(Tunable {
+                name: stringify!($field),
+                default: $default,
+                min: $min,
+                max: $max,
+            },)*];
+
+            /// Value of a numeric parameter, or `None` if there is none by that name
+            pub fn get(&self, name: &str) -> Option<i32> {
+                match name {
+                    $(stringify!($field) => Some(self.$field as i32),)*
+                    _ => None,
+                }
+            }
+
+            /// Set a numeric parameter, returning whether there is one by that name. The value is
+            /// clamped to the parameter's range
+            pub fn set(&mut self, name: &str, value: i32) -> bool {
+                match name {
+                    $(stringify!($field) => self.$field = value.clamp($min, $max) as $ty,)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    };
+}
+
+search_params! {
+    /// Margin per ply above beta at which reverse futility pruning cuts a node
+    reverse_futility_margin: i32 = 120, 0..=400;
+    reverse_futility_depth: usize = 3, 0..=8;
+    /// Margin per ply below alpha at which quiet moves are futile
+    futility_margin: i32 = 150, 0..=400;
+    futility_depth: usize = 3, 0..=8;
+    /// Margin per ply below alpha at which a node drops straight into quiescence
+    razoring_margin: i32 = 300, 0..=800;
+    razoring_depth: usize = 2, 0..=6;
+    /// Margin on top of the captured piece's value for a capture to be considered in quiescence
+    delta_margin: i32 = 200, 0..=800;
+    /// Half-width of the initial aspiration window around the previous iteration's score
+    aspiration_window: i32 = 50, 5..=200;
+    /// Shallowest depth at which aspiration windows are used, below this the score is too unstable
+    aspiration_depth: usize = 4, 1..=10;
+    /// Shallowest depth at which the transposition table move is tested for singularity
+    singular_depth: usize = 6, 2..=12;
+    /// Margin per ply below the transposition table score that the other moves must fail to reach
+    singular_margin: i32 = 2, 0..=20;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tunable() {
+        let mut params = SearchParams::default();
+        for tunable in SearchParams::TUNABLE {
+            assert_eq!(params.get(tunable.name), Some(tunable.default));
+            assert!((tunable.min..=tunable.max).contains(&tunable.default));
+        }
+
+        assert!(params.set("futility_margin", 175));
+        assert_eq!(params.futility_margin, 175);
+        assert!(params.set("razoring_depth", -1));
+        assert_eq!(params.razoring_depth, 0);
+        assert!(!params.set("quiescence_checks", 1));
+        assert_eq!(params.get("unknown"), None);
+    }
+}
@@ -0,0 +1,217 @@
+use super::evaluate::{TermScores, add};
+use super::{Engine, EvalParams};
+use crate::calculated::PAWN_ATTACKS;
+use crate::repr::Board;
+use crate::repr::bitboards::*;
+use crate::repr::constants::*;
+
+/// Number of entries in the pawn hash table, which hits often as pawn structures change slowly
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+/// Evaluation of the terms depending only on the pawns of a position
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct PawnEntry {
+    hash: u64,
+    scores: TermScores,
+    /// Passed pawns of both colours
+    passed: u64,
+}
+
+/// Pawn structure evaluations indexed by the pawn hash of the position
+pub struct PawnTable {
+    entries: Box<[PawnEntry]>,
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self {
+            entries: vec![PawnEntry::default(); PAWN_TABLE_SIZE].into_boxed_slice(),
+        }
+    }
+}
+
+impl PawnTable {
+    pub fn clear(&mut self) {
+        self.entries.fill(PawnEntry::default());
+    }
+
+    /// Cached pawn structure of a position, evaluating and storing it if missing. A position
+    /// without pawns matches the empty entry, which is also its correct evaluation
+    fn probe(&mut self, board: &Board, params: &EvalParams) -> PawnEntry {
+        let entry = &mut self.entries[board.pawn_hash as usize % PAWN_TABLE_SIZE];
+        if entry.hash != board.pawn_hash {
+            *entry = evaluate_structure(board, params);
+        }
+        *entry
+    }
+}
+
+impl Engine {
+    /// Midgame and endgame pawn structure scores of each colour
+    pub(super) fn evaluate_pawns(&mut self) -> TermScores {
+        let entry = self.pawn_table.probe(&self.board, &self.eval_params);
+        let mut scores = entry.scores;
+
+        for colour in [WHITE, BLACK] {
+            let mut passed = entry.passed & self.board.pieces[colour as usize][PAWN as usize];
+            while passed != 0 {
+                let square = passed.trailing_zeros() as u8;
+                passed &= passed - 1;
+
+                scores[colour as usize] =
+                    add(scores[colour as usize], self.passed_pawn(colour, square));
+            }
+        }
+
+        scores
+    }
+
+    /// Terms for a passed pawn that depend on the pieces around it, so can't be cached
+    fn passed_pawn(&self, colour: u8, square: u8) -> (i32, i32) {
+        let params = &self.eval_params;
+        let rank = relative_rank(colour, square);
+        let stop = if colour == WHITE {
+            square + 8
+        } else {
+            square - 8
+        };
+        let (mut midgame, mut endgame) = (0, 0);
+
+        if self.board.squares[stop as usize].is_some() {
+            midgame += params.blocked_passed_pawn.0;
+            endgame += params.blocked_passed_pawn.1;
+        }
+
+        // Kings matter more the closer the pawn is to promoting
+        let weight = (rank as i32 - 2).max(0);
+        if let (Some(own), Some(enemy)) = (
+            self.board.king_square(colour),
+            self.board.king_square(1 - colour),
+        ) {
+            endgame += weight
+                * (params.passed_pawn_own_king_distance * distance(own, stop)
+                    + params.passed_pawn_enemy_king_distance * distance(enemy, stop));
+        }
+
+        let path = forward_ranks(colour, square) & file_mask(square);
+        if path & self.board.blockers() == 0
+            && (A1..=H8)
+                .filter(|&square| path & 1 << square != 0)
+                .all(|square| !self.board.is_attacked(square, 1 - colour))
+        {
+            endgame += params.free_passed_pawn[rank];
+        }
+
+        (midgame, endgame)
+    }
+}
+
+fn evaluate_structure(board: &Board, params: &EvalParams) -> PawnEntry {
+    let mut entry = PawnEntry {
+        hash: board.pawn_hash,
+        ..Default::default()
+    };
+
+    for colour in [WHITE, BLACK] {
+        let own = board.pieces[colour as usize][PAWN as usize];
+        let enemy = board.pieces[1 - colour as usize][PAWN as usize];
+
+        let mut pawns = own;
+        while pawns != 0 {
+            let square = pawns.trailing_zeros() as u8;
+            pawns &= pawns - 1;
+
+            let rank = relative_rank(colour, square);
+            let file = file_mask(square);
+            let adjacent = adjacent_files(square);
+            let front = forward_ranks(colour, square);
+            let mut score = (0, 0);
+
+            if front & (file | adjacent) & enemy == 0 && front & file & own == 0 {
+                entry.passed |= 1 << square;
+                score = add(score, params.passed_pawn[rank]);
+            }
+
+            if front & file & own != 0 {
+                score = add(score, params.doubled_pawn);
+            }
+
+            let stop = if colour == WHITE {
+                square + 8
+            } else {
+                square - 8
+            };
+            if adjacent & own == 0 {
+                score = add(score, params.isolated_pawn);
+            } else if adjacent & !front & own == 0
+                && PAWN_ATTACKS[colour as usize][stop as usize] & enemy != 0
+            {
+                // No pawn can come up to defend it and it can't advance safely
+                score = add(score, params.backward_pawn);
+            }
+
+            if adjacent & rank_mask(square) & own != 0 {
+                score = add(score, params.phalanx_pawn[rank]);
+            }
+
+            if PAWN_ATTACKS[1 - colour as usize][square as usize] & own != 0 {
+                score = add(score, params.supported_pawn);
+            }
+
+            entry.scores[colour as usize] = add(entry.scores[colour as usize], score);
+        }
+    }
+
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(fen: &str) -> PawnEntry {
+        evaluate_structure(&Board::from_fen(fen).unwrap(), &EvalParams::default())
+    }
+
+    fn white(entry: PawnEntry) -> (i32, i32) {
+        entry.scores[WHITE as usize]
+    }
+
+    #[test]
+    fn passed_pawns() {
+        let entry = structure("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1");
+        assert_eq!(entry.passed, 1 << D5);
+
+        let entry = structure("4k3/2p5/8/3P4/8/8/8/4K3 w - - 0 1");
+        assert_eq!(entry.passed, 0);
+
+        // Only the front pawn of a doubled pair is passed
+        let entry = structure("4k3/8/8/3P4/3P4/8/8/4K3 w - - 0 1");
+        assert_eq!(entry.passed, 1 << D5);
+    }
+
+    #[test]
+    fn weaknesses() {
+        let healthy = structure("4k3/8/8/8/8/8/2PPP3/4K3 w - - 0 1");
+        let isolated = structure("4k3/8/8/8/8/8/P1P1P3/4K3 w - - 0 1");
+        let doubled = structure("4k3/8/8/8/8/2P5/2P1P3/4K3 w - - 0 1");
+        assert!(white(healthy).0 > white(isolated).0);
+        assert!(white(healthy).1 > white(doubled).1);
+
+        // Without the c3 pawn nothing can support d4, and d5 is covered by the c6 pawn
+        let backward = structure("4k3/8/2p5/4P3/3P4/8/8/4K3 w - - 0 1");
+        let supported = structure("4k3/8/2p5/4P3/3P4/2P5/8/4K3 w - - 0 1");
+        assert!(white(supported).0 > white(backward).0);
+    }
+
+    #[test]
+    fn symmetry() {
+        let board =
+            Board::from_fen("4k3/pp3p2/2p1p1p1/3p4/1P1P3P/P3P3/5PP1/4K3 w - - 0 1").unwrap();
+        let params = EvalParams::default();
+        let entry = evaluate_structure(&board, &params);
+        let mirrored = evaluate_structure(&board.mirror(), &params);
+        assert_eq!(entry.scores, [mirrored.scores[1], mirrored.scores[0]]);
+        assert_eq!(entry.passed.count_ones(), mirrored.passed.count_ones());
+    }
+}
@@ -0,0 +1,180 @@
+use super::Engine;
+use super::evaluate::{TermScores, add};
+use crate::calculated::values::*;
+use crate::calculated::{
+    PAWN_ATTACKS, generate_bishop_moves, generate_knight_moves, generate_rook_moves,
+};
+use crate::repr::bitboards::*;
+use crate::repr::constants::*;
+
+impl Engine {
+    /// Midgame and endgame mobility and piece placement scores of each colour
+    pub(super) fn evaluate_pieces(&self) -> TermScores {
+        [
+            self.evaluate_colour_pieces(WHITE),
+            self.evaluate_colour_pieces(BLACK),
+        ]
+    }
+
+    fn evaluate_colour_pieces(&self, colour: u8) -> (i32, i32) {
+        let own = self.board.pieces[colour as usize];
+        let enemy = self.board.pieces[1 - colour as usize];
+        let blockers = self.board.blockers();
+        let own_pawns = own[PAWN as usize];
+        let enemy_pawns = enemy[PAWN as usize];
+
+        // Squares a piece could move to without being taken by a pawn
+        let safe = !self.board.colour_pieces(colour) & !pawn_attacks(1 - colour, enemy_pawns);
+        let params = &self.eval_params;
+        let mut score = (0, 0);
+
+        if own[BISHOP as usize].count_ones() >= 2 {
+            score = add(score, params.bishop_pair);
+        }
+
+        for piece in KNIGHT..=QUEEN {
+            let mut pieces = own[piece as usize];
+            while pieces != 0 {
+                let square = pieces.trailing_zeros() as u8;
+                pieces &= pieces - 1;
+
+                let attacks = match piece {
+                    KNIGHT => generate_knight_moves(square),
+                    BISHOP => generate_bishop_moves(square, blockers),
+                    ROOK => generate_rook_moves(square, blockers),
+                    _ => {
+                        generate_bishop_moves(square, blockers)
+                            | generate_rook_moves(square, blockers)
+                    }
+                };
+                let mobility = (attacks & safe).count_ones() as i32;
+                let weight = params.mobility[piece as usize];
+                let surplus = mobility - MOBILITY_BASELINE[piece as usize];
+                score = add(score, (weight.0 * surplus, weight.1 * surplus));
+
+                match piece {
+                    KNIGHT if self.is_outpost(colour, square) => {
+                        score = add(score, params.knight_outpost);
+                    }
+                    BISHOP if self.is_trapped_bishop(colour, square) => {
+                        score = add(score, params.trapped_bishop);
+                    }
+                    ROOK => {
+                        let file = file_mask(square);
+                        if file & (own_pawns | enemy_pawns) == 0 {
+                            score = add(score, params.rook_open_file);
+                        } else if file & own_pawns == 0 {
+                            score = add(score, params.rook_semi_open_file);
+                        }
+
+                        if relative_rank(colour, square) == 6
+                            && (self
+                                .board
+                                .king_square(1 - colour)
+                                .is_some_and(|king| relative_rank(colour, king) == 7)
+                                || rank_mask(square) & enemy_pawns != 0)
+                        {
+                            score = add(score, params.rook_on_seventh);
+                        }
+
+                        if mobility <= 3 && self.is_trapped_rook(colour, square) {
+                            score = add(score, params.trapped_rook);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        score
+    }
+
+    /// Whether a square in the enemy half is defended by a pawn and can never be attacked by one
+    fn is_outpost(&self, colour: u8, square: u8) -> bool {
+        let own_pawns = self.board.pieces[colour as usize][PAWN as usize];
+        let enemy_pawns = self.board.pieces[1 - colour as usize][PAWN as usize];
+
+        (3..=5).contains(&relative_rank(colour, square))
+            && PAWN_ATTACKS[1 - colour as usize][square as usize] & own_pawns != 0
+            && forward_ranks(colour, square) & adjacent_files(square) & enemy_pawns == 0
+    }
+
+    fn is_trapped_bishop(&self, colour: u8, square: u8) -> bool {
+        let enemy_pawns = self.board.pieces[1 - colour as usize][PAWN as usize];
+        let relative = |square: u8| if colour == WHITE { square } else { square ^ 56 };
+
+        (square == relative(A7) && enemy_pawns & 1 << relative(B6) != 0)
+            || (square == relative(H7) && enemy_pawns & 1 << relative(G6) != 0)
+    }
+
+    /// Whether a rook is in the corner behind a king that has stepped aside instead of castling
+    fn is_trapped_rook(&self, colour: u8, square: u8) -> bool {
+        let Some(king) = self.board.king_square(colour) else {
+            return false;
+        };
+        let (king_file, rook_file) = (king % 8, square % 8);
+
+        relative_rank(colour, king) == 0
+            && relative_rank(colour, square) == 0
+            && ((king_file >= 5 && rook_file > king_file)
+                || (king_file <= 2 && rook_file < king_file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::Board;
+
+    fn pieces(fen: &str) -> (i32, i32) {
+        let mut engine = Engine::new();
+        engine.board = Board::from_fen(fen).unwrap();
+        engine.evaluate_pieces()[WHITE as usize]
+    }
+
+    #[test]
+    fn mobility() {
+        // A knight in the corner has far fewer squares than one in the centre
+        let corner = pieces("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+        let centre = pieces("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        assert!(centre.0 > corner.0);
+
+        // Squares covered by enemy pawns don't count
+        let free = pieces("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let covered = pieces("4k3/8/2p1p3/8/3N4/8/8/4K3 w - - 0 1");
+        assert!(free.0 > covered.0);
+    }
+
+    #[test]
+    fn rooks() {
+        let open = pieces("4k3/p7/8/8/8/8/P7/3RK3 w - - 0 1");
+        let closed = pieces("4k3/3p4/8/8/8/8/3P4/3RK3 w - - 0 1");
+        assert!(open.0 > closed.0);
+
+        let trapped = pieces("4k3/8/8/8/8/8/5PPP/5K1R w - - 0 1");
+        let castled = pieces("4k3/8/8/8/8/8/5PPP/5RK1 w - - 0 1");
+        assert!(castled.0 > trapped.0);
+    }
+
+    #[test]
+    fn outposts_and_trapped_bishops() {
+        let outpost = pieces("4k3/8/8/3N4/2P5/8/8/4K3 w - - 0 1");
+        let exposed = pieces("4k3/2p5/8/3N4/2P5/8/8/4K3 w - - 0 1");
+        assert!(outpost.0 > exposed.0);
+
+        let trapped = pieces("4k3/B7/1p6/8/8/8/8/4K3 w - - 0 1");
+        let free = pieces("4k3/B7/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(free.0 - trapped.0 >= -TRAPPED_BISHOP.0);
+    }
+
+    #[test]
+    fn symmetry() {
+        let mut engine = Engine::new();
+        engine.board =
+            Board::from_fen("r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP2BPPP/R1BQ1RK1 w - - 0 8")
+                .unwrap();
+        let [white, black] = engine.evaluate_pieces();
+        engine.board = engine.board.mirror();
+        assert_eq!(engine.evaluate_pieces(), [black, white]);
+    }
+}
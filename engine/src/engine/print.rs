@@ -1,32 +1,148 @@
 use std::fmt::Write;
 
+use super::transposition_table::Score;
+use super::wdl::wdl;
+use super::{Engine, MAX_PLY, Protocol};
 use crate::Move;
 use crate::calculated::values::MATE_VALUE;
-use crate::engine::MAX_PLY;
-
-pub fn print_info(
-    depth: usize,
-    nodes: usize,
-    score: i32,
-    pv_length: usize,
-    pv_table: &[Option<Move>; MAX_PLY],
-) {
-    let mut buffer = String::new();
-    write!(buffer, "info depth {depth} nodes {nodes} score ").unwrap();
 
+/// Score XBoard reads as a mate, with the number of moves to it added
+const XBOARD_MATE: i32 = 100_000;
+
+impl Engine {
+    /// Report the score and principal variation of the current iteration
+    pub(super) fn print_info(&self, score: Score) {
+        match self.options.protocol {
+            Protocol::Uci => println!("{}", self.uci_info(score)),
+            Protocol::Xboard => self.print_thinking(score),
+        }
+    }
+
+    fn uci_info(&self, score: Score) -> String {
+        let elapsed = self.search_start.elapsed();
+        let nps = (self.nodes as u128 * 1_000_000 / elapsed.as_micros().max(1)) as u64;
+
+        let mut buffer = String::new();
+        write!(
+            buffer,
+            "info depth {} seldepth {} time {} nodes {} nps {nps} hashfull {} score ",
+            self.root_depth,
+            self.seldepth,
+            elapsed.as_millis(),
+            self.nodes,
+            self.tt.hashfull(),
+        )
+        .unwrap();
+
+        let (score, bound) = match score {
+            Score::Exact(score) => (score, ""),
+            Score::Alpha(score) => (score, " upperbound"),
+            Score::Beta(score) => (score, " lowerbound"),
+        };
+
+        if let Some(mate) = mate_in(score) {
+            write!(buffer, "mate {mate}{bound} ").unwrap();
+        } else {
+            write!(buffer, "cp {score}{bound} ").unwrap();
+        }
+
+        if self.options.show_wdl {
+            let (win, draw, loss) = wdl(score, &self.board);
+            write!(buffer, "wdl {win} {draw} {loss} ").unwrap();
+        }
+
+        buffer.push_str("pv ");
+        self.write_pv(&mut buffer);
+        buffer
+    }
+
+    /// Report an iteration as CECP thinking output, `ply score time nodes pv` with the time in
+    /// centiseconds, leaving out the bounds from aspiration windows as the protocol has no way to
+    /// mark them
+    fn print_thinking(&self, score: Score) {
+        let Score::Exact(score) = score else {
+            return;
+        };
+
+        // Mates are reported as 100000 plus the number of moves, as XBoard expects
+        let score = match mate_in(score) {
+            Some(mate) if mate > 0 => XBOARD_MATE + mate,
+            Some(mate) => -XBOARD_MATE + mate,
+            None => score,
+        };
+
+        let mut buffer = format!(
+            "{} {score} {} {} ",
+            self.root_depth,
+            self.search_start.elapsed().as_millis() / 10,
+            self.nodes,
+        );
+        self.write_pv(&mut buffer);
+        println!("{buffer}");
+    }
+
+    fn write_pv(&self, buffer: &mut String) {
+        for pv_move in self.pv_table[0].iter().take(self.pv_length[0]).flatten() {
+            write!(buffer, "{} ", pv_move).unwrap();
+        }
+    }
+
+    /// Report the root move about to be searched, counting from one
+    pub(super) fn print_currmove(&self, mv: Move, number: usize) {
+        println!("{}", self.currmove_info(mv, number));
+    }
+
+    fn currmove_info(&self, mv: Move, number: usize) -> String {
+        format!(
+            "info depth {} currmove {mv} currmovenumber {number}",
+            self.root_depth
+        )
+    }
+}
+
+/// Number of moves until mate for a mate score, negative when the side to move is being mated
+pub fn mate_in(score: i32) -> Option<i32> {
     let dist = MATE_VALUE - score.abs();
 
-    if dist <= 64_i32 {
+    if dist <= MAX_PLY as i32 {
         let dist = if score > 0 { dist } else { -dist };
-        let mate = if dist > 0 { dist + 1 } else { dist } / 2;
-        write!(buffer, "mate {mate} pv ").unwrap();
+        Some(if dist > 0 { dist + 1 } else { dist } / 2)
     } else {
-        write!(buffer, "cp {score} pv ").unwrap();
+        None
     }
+}
 
-    for pv_move in pv_table.iter().take(pv_length).flatten() {
-        write!(buffer, "{} ", pv_move).unwrap();
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uci_info() {
+        let mut engine = Engine::new();
+        engine.silent = true;
+        engine.search_depth(4);
 
-    println!("{buffer}");
+        let info = engine.uci_info(Score::Exact(35));
+        let fields: Vec<&str> = info.split_whitespace().collect();
+        let field = |name: &str| {
+            let index = fields.iter().position(|field| *field == name).unwrap();
+            fields[index + 1]
+        };
+        assert!(info.starts_with("info depth 4 seldepth "));
+        assert!(field("seldepth").parse::<usize>().unwrap() >= 4);
+        assert!(field("time").parse::<u64>().is_ok());
+        assert_eq!(field("nodes"), engine.nodes.to_string());
+        assert!(field("nps").parse::<u64>().unwrap() > 0);
+        assert!(info.contains(" score cp 35 pv "));
+        assert!(!info.contains(" wdl "));
+
+        engine.options.show_wdl = true;
+        let info = engine.uci_info(Score::Beta(MATE_VALUE - 3));
+        assert!(info.contains(" score mate 2 lowerbound wdl 1000 0 0 pv "));
+
+        assert_eq!(
+            engine.currmove_info(Move::coordinate("e2e4"), 3),
+            "info depth 4 currmove e2e4 currmovenumber 3"
+        );
+    }
 }
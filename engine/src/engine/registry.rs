@@ -0,0 +1,242 @@
+#[cfg(feature = "tune")]
+use super::SearchParams;
+use super::{DEFAULT_HASH_MB, Engine, EngineOptions};
+use crate::nnue::Network;
+
+const MAX_HASH_MB: i64 = 1024;
+/// The search is single threaded and reports a single line, so these can't be raised yet
+const MAX_THREADS: i64 = 1;
+const MAX_MULTI_PV: i64 = 1;
+const MAX_MOVE_OVERHEAD: i64 = 5000;
+
+/// Type of an option along with its default and the values it accepts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionType {
+    Check {
+        default: bool,
+    },
+    Spin {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    Combo {
+        default: &'static str,
+        vars: &'static [&'static str],
+    },
+    Button,
+    String {
+        default: &'static str,
+    },
+}
+
+/// A value given to an option, checked against its type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionValue {
+    Check(bool),
+    Spin(i64),
+    Combo(String),
+    Button,
+    String(String),
+}
+
+type Apply = Box<dyn Fn(&mut Engine, OptionValue) -> Result<(), String>>;
+
+/// An option advertised to the GUI, whichever protocol it speaks, and how to apply it to the
+/// engine
+pub struct GuiOption {
+    pub name: &'static str,
+    pub option_type: OptionType,
+    apply: Apply,
+}
+
+impl GuiOption {
+    pub fn check(
+        name: &'static str,
+        default: bool,
+        apply: impl Fn(&mut Engine, bool) + 'static,
+    ) -> Self {
+        Self {
+            name,
+            option_type: OptionType::Check { default },
+            apply: Box::new(move |engine, value| {
+                if let OptionValue::Check(value) = value {
+                    apply(engine, value);
+                }
+                Ok(())
+            }),
+        }
+    }
+
+    pub fn spin(
+        name: &'static str,
+        default: i64,
+        min: i64,
+        max: i64,
+        apply: impl Fn(&mut Engine, i64) + 'static,
+    ) -> Self {
+        Self {
+            name,
+            option_type: OptionType::Spin { default, min, max },
+            apply: Box::new(move |engine, value| {
+                if let OptionValue::Spin(value) = value {
+                    apply(engine, value);
+                }
+                Ok(())
+            }),
+        }
+    }
+
+    pub fn combo(
+        name: &'static str,
+        default: &'static str,
+        vars: &'static [&'static str],
+        apply: impl Fn(&mut Engine, &str) + 'static,
+    ) -> Self {
+        Self {
+            name,
+            option_type: OptionType::Combo { default, vars },
+            apply: Box::new(move |engine, value| {
+                if let OptionValue::Combo(value) = value {
+                    apply(engine, &value);
+                }
+                Ok(())
+            }),
+        }
+    }
+
+    pub fn button(name: &'static str, apply: impl Fn(&mut Engine) + 'static) -> Self {
+        Self {
+            name,
+            option_type: OptionType::Button,
+            apply: Box::new(move |engine, _| {
+                apply(engine);
+                Ok(())
+            }),
+        }
+    }
+
+    /// A string option, which is the only kind that can fail to apply once parsed
+    pub fn string(
+        name: &'static str,
+        default: &'static str,
+        apply: impl Fn(&mut Engine, &str) -> Result<(), String> + 'static,
+    ) -> Self {
+        Self {
+            name,
+            option_type: OptionType::String { default },
+            apply: Box::new(move |engine, value| match value {
+                OptionValue::String(value) => apply(engine, &value),
+                _ => Ok(()),
+            }),
+        }
+    }
+
+    /// Check a value sent by the GUI against the option's type
+    pub fn parse(&self, value: Option<&str>) -> Result<OptionValue, String> {
+        let name = self.name;
+        match (&self.option_type, value) {
+            (OptionType::Button, _) => Ok(OptionValue::Button),
+            (_, None) => Err(format!("expected a value for {name}")),
+            (OptionType::Check { .. }, Some(value)) => match value.to_lowercase().as_str() {
+                "true" => Ok(OptionValue::Check(true)),
+                "false" => Ok(OptionValue::Check(false)),
+                _ => Err(format!("expected true or false for {name}: {value}")),
+            },
+            (OptionType::Spin { min, max, .. }, Some(value)) => value
+                .parse()
+                .ok()
+                .filter(|value| (min..=max).contains(&value))
+                .map(OptionValue::Spin)
+                .ok_or_else(|| format!("expected {name} between {min} and {max}: {value}")),
+            (OptionType::Combo { vars, .. }, Some(value)) => vars
+                .iter()
+                .find(|var| var.eq_ignore_ascii_case(value))
+                .map(|var| OptionValue::Combo(var.to_string()))
+                .ok_or_else(|| format!("expected one of {} for {name}: {value}", vars.join(", "))),
+            // GUIs send `<empty>` to clear a string
+            (OptionType::String { .. }, Some("<empty>")) => Ok(OptionValue::String(String::new())),
+            (OptionType::String { .. }, Some(value)) => Ok(OptionValue::String(value.to_string())),
+        }
+    }
+
+    /// Parse a value and apply it to the engine
+    pub fn set(&self, engine: &mut Engine, value: Option<&str>) -> Result<(), String> {
+        let value = self.parse(value)?;
+        (self.apply)(engine, value)
+    }
+}
+
+/// Every option the engine supports, in the order they are advertised
+pub fn registry() -> Vec<GuiOption> {
+    let defaults = EngineOptions::default();
+    #[allow(unused_mut)]
+    let mut options = vec![
+        GuiOption::spin(
+            "Hash",
+            DEFAULT_HASH_MB as i64,
+            1,
+            MAX_HASH_MB,
+            |engine, megabytes| engine.set_hash_size(megabytes as usize),
+        ),
+        GuiOption::button("Clear Hash", Engine::clear_hash),
+        GuiOption::spin(
+            "Threads",
+            defaults.threads as i64,
+            1,
+            MAX_THREADS,
+            |engine, threads| {
+                engine.options.threads = threads as usize;
+            },
+        ),
+        GuiOption::spin(
+            "MultiPV",
+            defaults.multi_pv as i64,
+            1,
+            MAX_MULTI_PV,
+            |engine, lines| {
+                engine.options.multi_pv = lines as usize;
+            },
+        ),
+        GuiOption::spin(
+            "Move Overhead",
+            defaults.move_overhead as i64,
+            0,
+            MAX_MOVE_OVERHEAD,
+            |engine, ms| {
+                engine.options.move_overhead = ms as u64;
+            },
+        ),
+        GuiOption::check("Ponder", defaults.ponder, |engine, ponder| {
+            engine.options.ponder = ponder;
+        }),
+        GuiOption::check("UCI_ShowWDL", defaults.show_wdl, |engine, show_wdl| {
+            engine.options.show_wdl = show_wdl;
+        }),
+        GuiOption::check("UseNNUE", false, Engine::set_use_nnue),
+        // Clearing the file unloads the network
+        GuiOption::string("EvalFile", "", |engine, path| {
+            let network = (!path.is_empty())
+                .then(|| Network::load(path))
+                .transpose()
+                .map_err(|e| e.to_string())?;
+            engine.set_network(network);
+            Ok(())
+        }),
+    ];
+
+    #[cfg(feature = "tune")]
+    for tunable in SearchParams::TUNABLE {
+        options.push(GuiOption::spin(
+            tunable.name,
+            tunable.default as i64,
+            tunable.min as i64,
+            tunable.max as i64,
+            |engine, value| {
+                engine.params.set(tunable.name, value as i32);
+            },
+        ));
+    }
+
+    options
+}
@@ -1,178 +1,726 @@
-use super::print::print_info;
+use std::time::Instant;
+
+use super::print::mate_in;
 use super::transposition_table::Score;
-use super::{Engine, MAX_PLY};
+use super::{Engine, MAX_PLY, Protocol, SearchLimits};
 use crate::calculated::values::*;
 use crate::repr::Move;
+use crate::repr::constants::*;
 
 impl Engine {
-    pub fn search_depth(&mut self, depth: usize) {
+    /// Iteratively deepen up to the given depth, returning the score of the final iteration
+    pub fn search_depth(&mut self, depth: usize) -> i32 {
+        self.search(SearchLimits::depth(depth)).1
+    }
+
+    /// Iteratively deepen until the given number of nodes have been searched, returning the best
+    /// move and score of the last completed iteration
+    pub fn search_nodes(&mut self, nodes: usize) -> (Option<Move>, i32) {
+        self.search(SearchLimits::nodes(nodes))
+    }
+
+    /// Iteratively deepen until one of the limits is reached, returning the best move and score
+    /// of the last completed iteration
+    pub fn search(&mut self, mut limits: SearchLimits) -> (Option<Move>, i32) {
+        let root_moves = self.board.moves();
+        limits.searchmoves.retain(|mv| root_moves.contains(mv));
+        let depth = limits.depth.unwrap_or(MAX_PLY - 1).min(MAX_PLY - 1);
+        self.limits = limits;
+        self.search_start = Instant::now();
+        self.stopped = false;
+        self.tt.new_search();
         self.nodes = 0;
         self.killer_moves = ([None; MAX_PLY], [None; MAX_PLY]);
         self.history_moves = [[0; 64]; 12];
+        self.counter_moves = [[None; 64]; 12];
+        self.continuation_history.fill([[[0; 64]; 12]; 64]);
         self.pv_length = [0; MAX_PLY];
         self.pv_table = [[None; MAX_PLY]; MAX_PLY];
+        self.excluded_moves = [None; MAX_PLY];
 
+        let mut eval = 0;
+        let mut best_move = None;
         for current_depth in 1..=depth {
-            let eval = self.alpha_beta(current_depth, -20_000, 20_000);
-            print_info(
-                current_depth,
-                self.nodes,
-                eval,
-                self.pv_length[0],
-                &self.pv_table[0],
-            );
+            self.root_depth = current_depth;
+            self.seldepth = 0;
+            let iteration_eval = self.aspiration_search(current_depth, eval);
+
+            // An interrupted iteration hasn't looked at every move, so can't be trusted
+            if self.stopped {
+                break;
+            }
+            eval = iteration_eval;
+            best_move = self.pv_table[0][0];
+
+            if let Some(mate) = self.limits.mate
+                && mate_in(eval).is_some_and(|moves| moves > 0 && moves as usize <= mate)
+            {
+                break;
+            }
         }
 
-        if let Some(best_move) = self.pv_table[0][0] {
-            println!("bestmove {}", best_move);
+        // Infinite and ponder searches can't report their move until told to
+        while !self.signals.stopped() && (self.limits.infinite || self.signals.pondering()) {
+            std::thread::sleep(HOLD_POLL_INTERVAL);
         }
+
+        // A search stopped during its first iteration still has to play something
+        let best_move = best_move.or_else(|| {
+            self.limits
+                .searchmoves
+                .first()
+                .or(root_moves.first())
+                .copied()
+        });
+
+        if !self.silent && self.options.protocol == Protocol::Uci {
+            match best_move {
+                Some(best_move) => println!("bestmove {best_move}"),
+                None => println!("bestmove 0000"),
+            }
+        }
+
+        (best_move, eval)
     }
 
-    fn score_move(&mut self, mv: &Move) -> i32 {
-        if self.pv_table[0][self.ply] == Some(*mv) {
-            return 20_000;
+    /// Whether the search has reached its node or time limit, or been told to stop, and should
+    /// unwind without trusting any scores
+    fn should_stop(&mut self) -> bool {
+        if self.limits.nodes.is_some_and(|limit| self.nodes >= limit) {
+            self.stopped = true;
+        }
+
+        // Reading the clock is comparatively slow, and the first iteration always completes so
+        // there is a move to play. Time spent pondering counts towards the move, but only stops
+        // the search once the opponent has played the expected move
+        if self.nodes.is_multiple_of(TIME_CHECK_NODES)
+            && self.root_depth > 1
+            && (self.signals.stopped()
+                || !self.signals.pondering()
+                    && self
+                        .limits
+                        .movetime
+                        .is_some_and(|movetime| self.search_start.elapsed() >= movetime))
+        {
+            self.stopped = true;
         }
 
-        let (_, piece) = self.board.squares[mv.source.0 as usize]
-            .expect("valid moves always have a piece at source");
+        self.stopped
+    }
 
-        // FIXME: Does not identify en passant captures
-        if let Some((_, victim)) = self.board.squares[mv.destination.0 as usize] {
-            // Captures
-            MVV_LVA[piece as usize][victim as usize] + 10_000
+    /// Search a window centred on the previous iteration's score, widening it on either side
+    /// whenever the true score falls outside of it
+    fn aspiration_search(&mut self, depth: usize, previous: i32) -> i32 {
+        let mut delta = self.params.aspiration_window;
+        let (mut alpha, mut beta) = if depth >= self.params.aspiration_depth && !is_mate(previous) {
+            (previous - delta, previous + delta)
         } else {
-            // Quiet
-            if self.killer_moves.0[self.board.half_moves as usize] == Some(*mv) {
-                9000
-            } else if self.killer_moves.1[self.board.half_moves as usize] == Some(*mv) {
-                8000
+            (-INFINITY, INFINITY)
+        };
+
+        loop {
+            let eval = self.alpha_beta(depth, alpha, beta);
+            if self.stopped {
+                return eval;
+            }
+
+            let score = if eval <= alpha && alpha > -INFINITY {
+                // Fail low, pull beta in towards the failed bound and push alpha further out
+                beta = (alpha + beta) / 2;
+                alpha = if is_mate(eval) {
+                    -INFINITY
+                } else {
+                    (eval - delta).max(-INFINITY)
+                };
+                Score::Alpha(eval)
+            } else if eval >= beta && beta < INFINITY {
+                beta = if is_mate(eval) {
+                    INFINITY
+                } else {
+                    (eval + delta).min(INFINITY)
+                };
+                Score::Beta(eval)
             } else {
-                self.history_moves[piece as usize][mv.destination.0 as usize]
+                Score::Exact(eval)
+            };
+
+            if !self.silent {
+                self.print_info(score);
             }
+
+            if let Score::Exact(eval) = score {
+                return eval;
+            }
+
+            delta *= 2;
         }
     }
 
-    fn alpha_beta(&mut self, depth: usize, mut alpha: i32, beta: i32) -> i32 {
-        if let Some(score) = self.tt.get(&self.board, depth, alpha, beta) {
-            return score;
+    fn alpha_beta(&mut self, depth: usize, mut alpha: i32, mut beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
         }
 
+        // Excluded move searches share the position's hash, so must not touch the table
+        let excluded = self.excluded_moves[self.ply];
+
         self.pv_length[self.ply] = self.ply;
 
+        // The root is always searched so that the principal variation is populated
+        if self.ply > 0
+            && excluded.is_none()
+            && let Some(score) = self.tt.get(&self.board, depth, self.ply, alpha, beta)
+        {
+            return score;
+        }
+
+        if self.ply >= MAX_PLY - 1 {
+            return self.evaluate();
+        }
+
+        // Mate distance pruning: no line from here can beat a mate that has already been found
+        if self.ply > 0 {
+            alpha = alpha.max(-MATE_VALUE + self.ply as i32);
+            beta = beta.min(MATE_VALUE - self.ply as i32 - 1);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
         if depth == 0 {
-            let eval = self.quiescence(alpha, beta);
-            self.tt.insert(&self.board, depth, Score::Exact(eval));
-            return eval;
+            return self.quiescence(alpha, beta, self.params.quiescence_checks);
         }
 
         let mut score = Score::Alpha(alpha);
         let mut found_pv = false;
 
         self.nodes += 1;
+        self.seldepth = self.seldepth.max(self.ply);
 
+        let in_check = self.board.in_check();
         let moves = self.board.moves();
         if moves.is_empty() {
-            if self.board.in_check() {
+            if in_check {
                 return -MATE_VALUE + self.ply as i32;
             }
             return 0;
         }
 
+        let tt_node = self.tt.probe(&self.board, self.ply);
+        let tt_move = tt_node.and_then(|node| node.best_move);
+
+        let pv_node = beta - alpha > 1;
+        let mut futile = false;
+        let mut stored_eval = None;
+
+        if !pv_node && !in_check && self.ply > 0 && excluded.is_none() {
+            let static_eval = tt_node
+                .and_then(|node| node.static_eval)
+                .unwrap_or_else(|| self.evaluate());
+            stored_eval = Some(static_eval);
+
+            // Reverse futility pruning: so far above beta that no reply will bring the score back
+            if depth <= self.params.reverse_futility_depth
+                && !is_mate(beta)
+                && static_eval - self.params.reverse_futility_margin * depth as i32 >= beta
+            {
+                return beta;
+            }
+
+            // Razoring: so far below alpha that only captures could recover the score
+            if depth <= self.params.razoring_depth
+                && static_eval + self.params.razoring_margin * depth as i32 <= alpha
+                && self.quiescence(alpha, beta, false) <= alpha
+            {
+                return alpha;
+            }
+
+            // Futility pruning: quiet moves can't raise the score above alpha
+            futile = depth <= self.params.futility_depth
+                && !is_mate(alpha)
+                && static_eval + self.params.futility_margin * depth as i32 <= alpha;
+        }
+
+        let singular_move = if excluded.is_none() {
+            self.singular_move(depth)
+        } else {
+            None
+        };
+
+        let mut best_move = None;
+        let mut failed_quiets = Vec::new();
+        let mut move_number = 0;
         let mut sorted_moves = self.board.moves();
-        sorted_moves.sort_by_key(|mv| std::cmp::Reverse(self.score_move(mv)));
+        sorted_moves.sort_by_key(|mv| std::cmp::Reverse(self.score_move(mv, tt_move)));
         for mv in sorted_moves {
+            if excluded == Some(mv) {
+                continue;
+            }
+            if self.ply == 0
+                && !self.limits.searchmoves.is_empty()
+                && !self.limits.searchmoves.contains(&mv)
+            {
+                continue;
+            }
+
+            move_number += 1;
+            // Root moves are only worth reporting once a search has been running a while
+            if self.ply == 0
+                && !self.silent
+                && self.options.protocol == Protocol::Uci
+                && self.search_start.elapsed() >= CURRMOVE_DELAY
+            {
+                self.print_currmove(mv, move_number);
+            }
+
+            let extend_singular = singular_move == Some(mv);
+            // Recaptures are common enough that extending them everywhere explodes the tree
+            let extend_recapture = beta - alpha > 1 && self.is_recapture(&mv);
+            let extend_pawn_push = self.is_seventh_rank_push(&mv);
+            let quiet = self.board.captured_piece(&mv).is_none() && mv.promotion.is_none();
+
             self.board.make_move(&mv);
             self.ply += 1;
 
+            let gives_check = self.board.in_check();
+            if futile && quiet && !gives_check {
+                self.board.unmake_move();
+                self.ply -= 1;
+                continue;
+            }
+
+            // At most one ply of extension is granted per move, and only within the path limit
+            let extension = if self.ply < EXTENSION_LIMIT * self.root_depth
+                && self.ply + depth < MAX_PLY
+                && (extend_singular || extend_recapture || extend_pawn_push || gives_check)
+            {
+                1
+            } else {
+                0
+            };
+            let new_depth = depth - 1 + extension;
+
             let eval = if found_pv {
-                let eval = -self.alpha_beta(depth - 1, -alpha - 1, -alpha);
+                let eval = -self.alpha_beta(new_depth, -alpha - 1, -alpha);
                 if (eval > alpha) && (eval < beta) {
                     // Failed to prove move is worse than current, re-search normally
-                    -self.alpha_beta(depth - 1, -beta, -alpha)
+                    -self.alpha_beta(new_depth, -beta, -alpha)
                 } else {
                     eval
                 }
             } else {
-                -self.alpha_beta(depth - 1, -beta, -alpha)
+                -self.alpha_beta(new_depth, -beta, -alpha)
             };
 
             self.board.unmake_move();
             self.ply -= 1;
 
+            if self.stopped {
+                return 0;
+            }
+
             if eval >= beta {
-                self.tt.insert(&self.board, depth, Score::Beta(beta));
+                // A clamped beta from mate distance pruning is exact to the parent, so needs a PV
+                self.update_pv(mv);
 
-                if self.board.squares[mv.destination.0 as usize].is_none() {
-                    self.killer_moves.1[self.ply] = self.killer_moves.0[self.ply];
-                    self.killer_moves.0[self.ply] = Some(mv);
+                if excluded.is_none() {
+                    self.tt.insert(
+                        &self.board,
+                        depth,
+                        self.ply,
+                        Score::Beta(beta),
+                        Some(mv),
+                        stored_eval,
+                    );
+                }
+
+                if quiet {
+                    self.update_quiet_histories(mv, &failed_quiets, depth);
                 }
 
                 return beta;
             }
 
-            if eval > alpha {
-                if self.board.squares[mv.destination.0 as usize].is_none() {
-                    let (_, piece) = self.board.squares[mv.source.0 as usize]
-                        .expect("all valid moves have a piece at source");
-                    self.history_moves[piece as usize][mv.destination.0 as usize] += depth as i32;
-                }
+            if quiet {
+                failed_quiets.push(mv);
+            }
 
+            if eval > alpha {
                 alpha = eval;
                 score = Score::Exact(alpha);
+                best_move = Some(mv);
                 found_pv = true;
 
-                self.pv_table[self.ply][self.ply] = Some(mv);
-                for next_ply in (self.ply + 1)..self.pv_length[self.ply + 1] {
-                    self.pv_table[self.ply][next_ply] = self.pv_table[self.ply + 1][next_ply];
-                }
-                self.pv_length[self.ply] = self.pv_length[self.ply + 1];
+                self.update_pv(mv);
             }
         }
 
-        self.tt.insert(&self.board, depth, score);
+        if excluded.is_none() {
+            self.tt
+                .insert(&self.board, depth, self.ply, score, best_move, stored_eval);
+        }
 
         alpha
     }
 
-    fn quiescence(&mut self, mut alpha: i32, beta: i32) -> i32 {
-        self.nodes += 1;
+    /// Make the move the head of this ply's principal variation, followed by the child's
+    fn update_pv(&mut self, mv: Move) {
+        self.pv_table[self.ply][self.ply] = Some(mv);
+        for next_ply in (self.ply + 1)..self.pv_length[self.ply + 1] {
+            self.pv_table[self.ply][next_ply] = self.pv_table[self.ply + 1][next_ply];
+        }
+        self.pv_length[self.ply] = self.pv_length[self.ply + 1];
+    }
 
-        let moves = self.board.moves();
-        if moves.is_empty() {
-            if self.board.in_check() {
-                return -MATE_VALUE + self.ply as i32;
-            }
+    /// Find the transposition table move if it is singular, that is every other move fails low
+    /// against a margin below its stored score when searched to a reduced depth
+    fn singular_move(&mut self, depth: usize) -> Option<Move> {
+        if self.ply == 0 || depth < self.params.singular_depth {
+            return None;
+        }
+
+        let node = self.tt.probe(&self.board, self.ply)?;
+        let tt_move = node.best_move?;
+        let tt_score = match node.score {
+            Score::Exact(score) | Score::Beta(score) => score,
+            Score::Alpha(_) => return None,
+        };
+        if node.depth + 3 < depth || is_mate(tt_score) {
+            return None;
+        }
+
+        let singular_beta = tt_score - self.params.singular_margin * depth as i32;
+
+        self.excluded_moves[self.ply] = Some(tt_move);
+        let eval = self.alpha_beta((depth - 1) / 2, singular_beta - 1, singular_beta);
+        self.excluded_moves[self.ply] = None;
+
+        // The verification search overwrote this ply's principal variation
+        self.pv_length[self.ply] = self.ply;
+
+        (eval < singular_beta).then_some(tt_move)
+    }
+
+    /// Whether a move captures back on the square where the previous move captured
+    fn is_recapture(&self, mv: &Move) -> bool {
+        self.board.squares[mv.destination.0 as usize].is_some()
+            && self
+                .board
+                .previous_move(1)
+                .is_some_and(|last| last.captured.is_some() && last.destination == mv.destination)
+    }
+
+    /// Whether a move pushes a pawn to its seventh rank, where it is always passed
+    fn is_seventh_rank_push(&self, mv: &Move) -> bool {
+        match self.board.squares[mv.source.0 as usize] {
+            Some((WHITE, PAWN)) => (A7..=H7).contains(&mv.destination.0),
+            Some((BLACK, PAWN)) => (A2..=H2).contains(&mv.destination.0),
+            _ => false,
+        }
+    }
+
+    /// Search captures and queen promotions until the position is quiet, so that the static
+    /// evaluation isn't taken in the middle of an exchange. Every evasion is searched when in check,
+    /// and quiet checks are searched too when `checks` is set.
+    fn quiescence(&mut self, mut alpha: i32, beta: i32, checks: bool) -> i32 {
+        if self.should_stop() {
             return 0;
         }
 
-        let eval = self.evaluate();
-        if eval >= beta {
-            return beta;
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(self.ply);
+
+        if self.ply >= MAX_PLY - 1 {
+            return self.evaluate();
         }
-        if eval > alpha {
-            alpha = eval;
+
+        if let Some(score) = self.tt.get(&self.board, 0, self.ply, alpha, beta) {
+            return score;
         }
 
-        // FIXME: Does not identify en passant captures
-        let mut captures = moves
-            .into_iter()
-            .filter(|x| self.board.squares[x.destination.0 as usize].is_some())
-            .collect::<Vec<_>>();
-        captures.sort_by_key(|mv| std::cmp::Reverse(self.score_move(mv)));
-        for mv in captures {
+        let tt_node = self.tt.probe(&self.board, self.ply);
+        let tt_move = tt_node.and_then(|node| node.best_move);
+
+        let in_check = self.board.in_check();
+        let mut stand_pat = None;
+
+        let mut moves = if in_check {
+            let evasions = self.board.moves();
+            if evasions.is_empty() {
+                return -MATE_VALUE + self.ply as i32;
+            }
+            evasions
+        } else {
+            let eval = tt_node
+                .and_then(|node| node.static_eval)
+                .unwrap_or_else(|| self.evaluate());
+            if eval >= beta {
+                self.tt.insert(
+                    &self.board,
+                    0,
+                    self.ply,
+                    Score::Beta(beta),
+                    None,
+                    Some(eval),
+                );
+                return beta;
+            }
+            if eval > alpha {
+                alpha = eval;
+            }
+            stand_pat = Some(eval);
+
+            let mut moves = self.board.captures();
+            if checks {
+                moves.extend(self.quiet_checks());
+            }
+            moves
+        };
+
+        let mut score = Score::Alpha(alpha);
+        let mut best_move = None;
+
+        moves.sort_by_key(|mv| std::cmp::Reverse(self.score_move(mv, tt_move)));
+        for mv in moves {
+            // Evasions are all searched, as standing pat isn't an option when in check
+            if let Some(stand_pat) = stand_pat {
+                // Delta pruning: even winning the captured piece outright can't raise alpha
+                if mv.promotion.is_none()
+                    && let Some(victim) = self.board.captured_piece(&mv)
+                    && stand_pat + PIECE_VALUES[victim as usize] + self.params.delta_margin <= alpha
+                {
+                    continue;
+                }
+
+                // Moves losing material are very unlikely to be the reason a position is good
+                if mv.promotion.is_none() && self.board.see(&mv) < 0 {
+                    continue;
+                }
+            }
+
             self.board.make_move(&mv);
             self.ply += 1;
-            let eval = -self.quiescence(-beta, -alpha);
+            let eval = -self.quiescence(-beta, -alpha, false);
             self.board.unmake_move();
             self.ply -= 1;
 
+            if self.stopped {
+                return 0;
+            }
+
             if eval >= beta {
+                self.tt.insert(
+                    &self.board,
+                    0,
+                    self.ply,
+                    Score::Beta(beta),
+                    Some(mv),
+                    stand_pat,
+                );
                 return beta;
             }
             if eval > alpha {
                 alpha = eval;
+                score = Score::Exact(alpha);
+                best_move = Some(mv);
             }
         }
+
+        self.tt
+            .insert(&self.board, 0, self.ply, score, best_move, stand_pat);
+
         alpha
     }
+
+    /// Legal quiet moves that give check
+    fn quiet_checks(&mut self) -> Vec<Move> {
+        let mut moves = self.board.moves();
+        moves.retain(|mv| {
+            if self.board.captured_piece(mv).is_some() || mv.promotion.is_some() {
+                return false;
+            }
+
+            self.board.make_move(mv);
+            let gives_check = self.board.in_check();
+            self.board.unmake_move();
+            gives_check
+        });
+        moves
+    }
+}
+
+/// Whether a score represents a forced mate for either side
+pub(super) fn is_mate(score: i32) -> bool {
+    score.abs() >= MATE_VALUE - MAX_PLY as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SearchParams;
+    use crate::repr::Board;
+    use std::time::Duration;
+
+    fn mate_distance(fen: &str, depth: usize) -> Option<i32> {
+        let mut engine = Engine::new();
+        engine.silent = true;
+        engine.board = Board::from_fen(fen).unwrap();
+        mate_in(engine.search_depth(depth))
+    }
+
+    #[test]
+    fn mate_in_2() {
+        let fen = "4r3/1pp2rbk/6pn/4n3/P3BN1q/1PB2bPP/8/2Q1RRK1 b - - 0 31";
+        assert_eq!(mate_distance(fen, 4), Some(2));
+    }
+
+    #[test]
+    fn mated_in_2() {
+        let fen = "rn3r1k/p3qp2/bp2p2Q/3pP3/P2NR3/1Pb2NPP/5PB1/2R3K1 b - - 0 22";
+        assert_eq!(mate_distance(fen, 4), Some(-2));
+    }
+
+    #[test]
+    fn mate_in_3() {
+        let fen = "rn3r1k/p3qp2/bp2p2p/3pP3/P2NRQ2/1Pb2NPP/5PB1/2R3K1 w - - 1 22";
+        assert_eq!(mate_distance(fen, 5), Some(3));
+    }
+
+    #[test]
+    fn mate_in_4() {
+        let fen = "r1bqk1nr/pp1p2bp/4n3/2p1Npp1/5P2/2N1P1PP/PPP5/1RBQKB1R w Kkq - 0 10";
+        assert_eq!(mate_distance(fen, 6), Some(4));
+    }
+
+    #[test]
+    fn mate_in_5() {
+        let fen = "4rb1k/2pqn2p/6pn/ppp3N1/P1QP2b1/1P2p3/2B3PP/B3RRK1 w - - 0 24";
+        assert_eq!(mate_distance(fen, 7), Some(5));
+    }
+
+    #[test]
+    fn broken_mate() {
+        // The mate the `BROKEN_MATE` benchmark in perf is named after
+        let mut engine = Engine::new();
+        engine.silent = true;
+        engine.board =
+            Board::from_fen("r1bqk2r/2ppb1p1/n3P2p/8/2B1nP2/4P3/1PPP3P/RNBQK1NR w KQkq - 0 10")
+                .unwrap();
+        let (best_move, eval) = engine.search(SearchLimits::depth(5));
+        assert_eq!(best_move, Some(Move::coordinate("d1h5")));
+        assert_eq!(mate_in(eval), Some(3));
+    }
+
+    const POSITIONS: [&str; 3] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    ];
+
+    /// Best move and nodes searched for a position at a fixed depth with the given parameters
+    fn search_with(fen: &str, depth: usize, params: SearchParams) -> (Option<Move>, usize) {
+        let mut engine = Engine::new();
+        engine.silent = true;
+        engine.params = params;
+        engine.board = Board::from_fen(fen).unwrap();
+        let (best_move, _) = engine.search(SearchLimits::depth(depth));
+        (best_move, engine.nodes)
+    }
+
+    #[test]
+    fn aspiration_windows() {
+        let without = SearchParams {
+            aspiration_depth: MAX_PLY,
+            ..SearchParams::default()
+        };
+
+        // Narrower windows find the same moves, and cut off more than they have to re-search
+        let (mut nodes, mut nodes_without) = (0, 0);
+        for fen in POSITIONS {
+            let (best_move, searched) = search_with(fen, 6, SearchParams::default());
+            let (best_move_without, searched_without) = search_with(fen, 6, without);
+            assert_eq!(best_move, best_move_without);
+            nodes += searched;
+            nodes_without += searched_without;
+        }
+        assert!(nodes < nodes_without);
+    }
+
+    #[test]
+    fn pruning() {
+        let without = SearchParams {
+            reverse_futility_depth: 0,
+            futility_depth: 0,
+            razoring_depth: 0,
+            ..SearchParams::default()
+        };
+
+        // Pruning only skips moves that can't matter, so the best moves stay the same and a queen
+        // sacrifice that forces mate in three is still found
+        let tactic = "rn3r1k/p3qp2/bp2p2p/3pP3/P2NRQ2/1Pb2NPP/5PB1/2R3K1 w - - 1 22";
+        for fen in POSITIONS.into_iter().chain([tactic]) {
+            let (best_move, searched) = search_with(fen, 5, SearchParams::default());
+            let (best_move_without, searched_without) = search_with(fen, 5, without);
+            assert_eq!(best_move, best_move_without);
+            assert!(searched < searched_without);
+        }
+        assert_eq!(
+            search_with(tactic, 5, SearchParams::default()).0,
+            Some(Move::coordinate("f4h6"))
+        );
+    }
+
+    #[test]
+    fn node_limit() {
+        let mut engine = Engine::new();
+        engine.silent = true;
+        engine.board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let (best_move, _) = engine.search_nodes(10_000);
+        assert!(best_move.is_some_and(|mv| engine.board.moves().contains(&mv)));
+        assert!(engine.nodes <= 10_001);
+
+        // Fixed node searches are reproducible
+        let mut other = Engine::new();
+        other.silent = true;
+        other.board = engine.board.clone();
+        assert_eq!(other.search_nodes(10_000).0, best_move);
+    }
+
+    #[test]
+    fn limits() {
+        let mut engine = Engine::new();
+        engine.silent = true;
+
+        // Stops at the first mate short enough, rather than searching on to the depth limit
+        engine.board =
+            Board::from_fen("rn3r1k/p3qp2/bp2p2p/3pP3/P2NRQ2/1Pb2NPP/5PB1/2R3K1 w - - 1 22")
+                .unwrap();
+        let (_, eval) = engine.search(SearchLimits {
+            depth: Some(12),
+            mate: Some(3),
+            ..SearchLimits::default()
+        });
+        assert_eq!(mate_in(eval), Some(3));
+        assert!(engine.root_depth < 12);
+
+        // Only the given moves are considered at the root
+        engine.reset(None);
+        let searchmoves = vec![Move::coordinate("a2a3"), Move::coordinate("h2h4")];
+        let (best_move, _) = engine.search(SearchLimits {
+            depth: Some(4),
+            searchmoves: searchmoves.clone(),
+            ..SearchLimits::default()
+        });
+        assert!(best_move.is_some_and(|mv| searchmoves.contains(&mv)));
+
+        let start = Instant::now();
+        let (best_move, _) = engine.search(SearchLimits {
+            movetime: Some(Duration::from_millis(50)),
+            ..SearchLimits::default()
+        });
+        assert!(best_move.is_some());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
 }
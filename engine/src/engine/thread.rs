@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+
+use super::{Engine, SearchLimits};
+use crate::repr::Move;
+
+/// Stack for the search thread, as large as the main thread's so deep searches don't overflow it
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Flags a frontend uses to control a search running on another thread
+#[derive(Debug, Default)]
+pub struct SearchSignals {
+    /// Set to end the search as soon as possible, playing the last completed iteration's move
+    pub stop: AtomicBool,
+    /// Set while searching on the opponent's time, when the search mustn't stop on time or
+    /// report its move until the opponent has played the expected move
+    pub ponder: AtomicBool,
+}
+
+impl SearchSignals {
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn pondering(&self) -> bool {
+        self.ponder.load(Ordering::Relaxed)
+    }
+}
+
+/// An engine searching on its own thread so that the frontend can keep reading commands, and
+/// stop the search or answer the GUI while it runs
+pub struct SearchThread {
+    /// The engine while no search is running
+    engine: Option<Engine>,
+    /// The search running, which hands the engine back once it has finished
+    handle: Option<JoinHandle<Engine>>,
+    signals: Arc<SearchSignals>,
+}
+
+impl SearchThread {
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            signals: engine.signals.clone(),
+            engine: Some(engine),
+            handle: None,
+        }
+    }
+
+    /// The engine, waiting for any search to finish first
+    pub fn engine(&mut self) -> &mut Engine {
+        self.wait();
+        self.engine
+            .as_mut()
+            .expect("the engine is here once the search is joined")
+    }
+
+    /// Wait for the search to finish by itself
+    pub fn wait(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.engine = Some(handle.join().expect("search thread panicked"));
+        }
+    }
+
+    /// Whether a search is running, including one holding back its move until it is stopped
+    pub fn searching(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Start searching the engine's position, pondering until `ponderhit` if asked to, then pass
+    /// the best move to `then` on the search thread
+    pub fn start(
+        &mut self,
+        limits: SearchLimits,
+        ponder: bool,
+        then: impl FnOnce(&mut Engine, Option<Move>) + Send + 'static,
+    ) {
+        self.wait();
+        let mut engine = self
+            .engine
+            .take()
+            .expect("the engine is here once the search is joined");
+
+        // Set before the thread starts, so a `stop` sent straight after `go` can't be missed
+        self.signals.stop.store(false, Ordering::Relaxed);
+        self.signals.ponder.store(ponder, Ordering::Relaxed);
+        let handle = std::thread::Builder::new()
+            .name("search".into())
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                let (best_move, _) = engine.search(limits);
+                then(&mut engine, best_move);
+                engine
+            })
+            .expect("failed to spawn the search thread");
+        self.handle = Some(handle);
+    }
+
+    /// Stop the search and wait for it to report its move
+    pub fn stop(&mut self) {
+        self.signals.stop.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    /// The opponent played the move being pondered on, so the search carries on as a normal one
+    pub fn ponderhit(&self) {
+        self.signals.ponder.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn stopping() {
+        let mut engine = Engine::new();
+        engine.silent = true;
+        let mut thread = SearchThread::new(engine);
+
+        let (sender, receiver) = mpsc::channel();
+        thread.start(SearchLimits::default(), false, move |_, best_move| {
+            sender.send(best_move).unwrap();
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(thread.searching());
+
+        thread.stop();
+        assert!(!thread.searching());
+        assert!(receiver.recv().unwrap().is_some());
+        assert!(thread.engine().nodes > 0);
+    }
+
+    #[test]
+    fn holding_the_move() {
+        let mut engine = Engine::new();
+        engine.silent = true;
+        let mut thread = SearchThread::new(engine);
+
+        // The depth is reached straight away, but the move is held until the search is stopped
+        thread.start(
+            SearchLimits {
+                infinite: true,
+                ..SearchLimits::depth(1)
+            },
+            false,
+            |_, _| {},
+        );
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(thread.searching());
+        thread.stop();
+
+        // Or, when pondering, until the opponent plays the expected move
+        thread.start(SearchLimits::depth(1), true, |_, _| {});
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(thread.searching());
+        thread.ponderhit();
+        thread.wait();
+        assert_eq!(thread.engine().root_depth, 1);
+    }
+}
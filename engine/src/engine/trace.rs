@@ -0,0 +1,212 @@
+use std::fmt::Display;
+
+use super::Engine;
+use super::evaluate::{TermScores, taper};
+use crate::calculated::values::{SCALE_NORMAL, TOTAL_PHASE};
+use crate::repr::constants::*;
+
+/// A separately scored part of the evaluation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Term {
+    Material,
+    PieceSquares,
+    Pawns,
+    Pieces,
+    KingSafety,
+}
+
+impl Term {
+    pub const ALL: [Term; 5] = [
+        Term::Material,
+        Term::PieceSquares,
+        Term::Pawns,
+        Term::Pieces,
+        Term::KingSafety,
+    ];
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Term::Material => "Material",
+            Term::PieceSquares => "PST",
+            Term::Pawns => "Pawns",
+            Term::Pieces => "Pieces",
+            Term::KingSafety => "King safety",
+        };
+        f.pad(name)
+    }
+}
+
+/// Breakdown of a static evaluation into the midgame and endgame score of each term for each side
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalTrace {
+    pub terms: Vec<(Term, TermScores)>,
+    pub phase: i32,
+    /// Share of the endgame score kept for the material left, out of `SCALE_NORMAL`
+    pub scale: i32,
+    /// Score from White's point of view of a specialised endgame evaluator, which replaces the
+    /// terms when there is one
+    pub endgame: Option<i32>,
+    /// Scaled score from White's point of view of the network, which replaces the terms when one
+    /// is in use and there is no specialised endgame evaluator
+    pub nnue: Option<i32>,
+    /// Final evaluation relative to the side to move
+    pub score: i32,
+    pub active_colour: u8,
+}
+
+impl EvalTrace {
+    /// Midgame and endgame totals of every term for each side
+    pub fn totals(&self) -> TermScores {
+        let mut totals = [(0, 0); 2];
+        for (_, scores) in &self.terms {
+            for (total, score) in totals.iter_mut().zip(scores) {
+                *total = (total.0 + score.0, total.1 + score.1);
+            }
+        }
+        totals
+    }
+}
+
+impl Display for EvalTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separator = " -------------+-------------+-------------+-------------";
+        if self.nnue.is_some() && self.endgame.is_none() {
+            writeln!(f, "Handcrafted breakdown, unused as the network is in use")?;
+            writeln!(f)?;
+        }
+        writeln!(f, "         Term |    White    |    Black    |    Total")?;
+        writeln!(f, "              |   MG    EG  |   MG    EG  |   MG    EG")?;
+        writeln!(f, "{separator}")?;
+
+        let row =
+            |f: &mut std::fmt::Formatter<'_>, name: &dyn Display, [white, black]: TermScores| {
+                writeln!(
+                    f,
+                    " {name:>12} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}",
+                    white.0,
+                    white.1,
+                    black.0,
+                    black.1,
+                    white.0 - black.0,
+                    white.1 - black.1
+                )
+            };
+
+        for (term, scores) in &self.terms {
+            row(f, term, *scores)?;
+        }
+        writeln!(f, "{separator}")?;
+        row(f, &"Total", self.totals())?;
+        writeln!(f)?;
+
+        let side = if self.active_colour == WHITE {
+            "white"
+        } else {
+            "black"
+        };
+        writeln!(f, "Phase: {}/{TOTAL_PHASE}", self.phase)?;
+        writeln!(f, "Endgame scale: {}/{SCALE_NORMAL}", self.scale)?;
+        if let Some(score) = self.endgame {
+            writeln!(f, "Specialised endgame: {score} cp (white)")?;
+        } else if let Some(score) = self.nnue {
+            writeln!(f, "Network: {score} cp (white)")?;
+        }
+        write!(f, "Final evaluation: {} cp ({side} to move)", self.score)
+    }
+}
+
+impl Engine {
+    /// Static evaluation of the current position broken down term by term
+    pub fn trace(&mut self) -> EvalTrace {
+        let terms = Term::ALL.into_iter().zip(self.evaluate_terms()).collect();
+        let mut trace = EvalTrace {
+            terms,
+            phase: self.board.phase(),
+            scale: SCALE_NORMAL,
+            endgame: self.evaluate_endgame(),
+            nnue: None,
+            score: 0,
+            active_colour: self.board.active_colour,
+        };
+
+        let [white, black] = trace.totals();
+        let (midgame, endgame) = (white.0 - black.0, white.1 - black.1);
+        trace.scale = self.scale_factor(endgame);
+        trace.nnue = self
+            .board
+            .evaluate_nnue()
+            .map(|score| self.scale_nnue(score));
+        let score = trace
+            .endgame
+            .or(trace.nnue)
+            .unwrap_or_else(|| taper(midgame, endgame * trace.scale / SCALE_NORMAL, trace.phase));
+        trace.score = if trace.active_colour == WHITE {
+            score
+        } else {
+            -score
+        };
+        trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nnue::Network;
+    use crate::repr::Board;
+
+    const POSITIONS: [&str; 5] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "8/3b1k2/8/2p1p3/4P3/3PB3/5K2/8 b - - 0 1",
+        "k7/8/2K5/8/8/8/8/B5N1 w - - 0 1",
+    ];
+
+    #[test]
+    fn matches_evaluation() {
+        let mut engine = Engine::new();
+        for fen in POSITIONS {
+            engine.board = Board::from_fen(fen).unwrap();
+            let trace = engine.trace();
+            assert_eq!(trace.score, engine.evaluate(), "{fen}");
+            assert_eq!(trace.terms.len(), Term::ALL.len());
+            assert_eq!(trace.nnue, None);
+        }
+    }
+
+    #[test]
+    fn matches_network_evaluation() {
+        let mut engine = Engine::new();
+        engine.set_network(Some(Network::random(0)));
+        engine.set_use_nnue(true);
+        for fen in POSITIONS {
+            engine.reset(Some(fen.to_string()));
+            let trace = engine.trace();
+            assert!(trace.nnue.is_some());
+            assert_eq!(trace.score, engine.evaluate(), "{fen}");
+        }
+
+        // Outside of specialised endgames the terms are only shown for reference
+        engine.reset(None);
+        let trace = engine.trace().to_string();
+        assert!(trace.contains("unused as the network is in use"));
+        assert!(trace.contains("Network: "));
+    }
+
+    #[test]
+    fn starting_position() {
+        let mut engine = Engine::new();
+        let trace = engine.trace();
+        let [white, black] = trace.totals();
+        assert_eq!(white, black);
+        assert_eq!(trace.phase, TOTAL_PHASE);
+        assert!(
+            trace
+                .to_string()
+                .ends_with("Final evaluation: 0 cp (white to move)")
+        );
+    }
+}
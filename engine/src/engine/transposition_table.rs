@@ -1,4 +1,5 @@
-use crate::repr::Board;
+use super::search::is_mate;
+use crate::repr::{Board, Move};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Score {
@@ -7,29 +8,114 @@ pub enum Score {
     Beta(i32),
 }
 
+impl Score {
+    fn map(self, f: impl Fn(i32) -> i32) -> Score {
+        match self {
+            Score::Exact(score) => Score::Exact(f(score)),
+            Score::Alpha(score) => Score::Alpha(f(score)),
+            Score::Beta(score) => Score::Beta(f(score)),
+        }
+    }
+}
+
+/// Mate scores are relative to the root, but are stored relative to the node so that they stay
+/// correct when the position is reached at a different ply
+fn to_tt(score: i32, ply: usize) -> i32 {
+    if !is_mate(score) {
+        score
+    } else if score > 0 {
+        score + ply as i32
+    } else {
+        score - ply as i32
+    }
+}
+
+fn from_tt(score: i32, ply: usize) -> i32 {
+    if !is_mate(score) {
+        score
+    } else if score > 0 {
+        score - ply as i32
+    } else {
+        score + ply as i32
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Node {
-    hash: u64,
-    depth: usize,
-    score: Score,
+    pub hash: u64,
+    pub depth: usize,
+    pub score: Score,
+    pub best_move: Option<Move>,
+    pub static_eval: Option<i32>,
+    /// Search generation the node was last written in
+    age: u8,
 }
 
-// Statically define size of transposition table to 16 MB
-const TABLE_SIZE: usize = 16 * 1024 * 1024;
+/// Number of nodes sharing a single index, replacement picks between them
+const BUCKET_SIZE: usize = 4;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+type Bucket = [Option<Node>; BUCKET_SIZE];
 
 #[derive(Debug)]
-pub struct TranspositionTable(Box<[Option<Node>]>);
+pub struct TranspositionTable {
+    buckets: Box<[Bucket]>,
+    /// Incremented every search so that nodes from earlier searches are replaced first
+    age: u8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
 
 impl TranspositionTable {
-    pub fn new() -> Self {
-        TranspositionTable(vec![None; TABLE_SIZE].into_boxed_slice())
+    pub fn new(megabytes: usize) -> Self {
+        let buckets = (megabytes.max(1) * 1024 * 1024 / size_of::<Bucket>()).max(1);
+        TranspositionTable {
+            buckets: vec![[None; BUCKET_SIZE]; buckets].into_boxed_slice(),
+            age: 0,
+        }
     }
 
-    pub fn get(&self, board: &Board, depth: usize, alpha: i32, beta: i32) -> Option<i32> {
-        let probe = &self.0[board.hash as usize % TABLE_SIZE];
+    /// Discard every stored node
+    pub fn clear(&mut self) {
+        self.buckets.fill([None; BUCKET_SIZE]);
+        self.age = 0;
+    }
 
-        if let Some(node) = probe
-            && node.hash == board.hash
+    /// Mark the start of a new search, ageing every node already in the table
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    /// Permille of the table filled by the current search, sampled from the first buckets
+    pub fn hashfull(&self) -> usize {
+        let sample = self.buckets.len().min(1000);
+        let filled = self.buckets[..sample]
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|node| node.age == self.age)
+            .count();
+        filled * 1000 / (sample * BUCKET_SIZE)
+    }
+
+    fn bucket(&self, board: &Board) -> usize {
+        board.hash as usize % self.buckets.len()
+    }
+
+    pub fn get(
+        &self,
+        board: &Board,
+        depth: usize,
+        ply: usize,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<i32> {
+        if let Some(node) = self.probe(board, ply)
             && node.depth >= depth
         {
             return match node.score {
@@ -42,12 +128,144 @@ impl TranspositionTable {
         None
     }
 
-    pub fn insert(&mut self, board: &Board, depth: usize, score: Score) {
-        // Using an always replace schema
-        self.0[board.hash as usize % TABLE_SIZE] = Some(Node {
+    /// Retrieve the stored node for a position regardless of its depth or bounds
+    pub fn probe(&self, board: &Board, ply: usize) -> Option<Node> {
+        self.buckets[self.bucket(board)]
+            .iter()
+            .flatten()
+            .find(|node| node.hash == board.hash)
+            .map(|node| Node {
+                score: node.score.map(|score| from_tt(score, ply)),
+                ..*node
+            })
+    }
+
+    pub fn insert(
+        &mut self,
+        board: &Board,
+        depth: usize,
+        ply: usize,
+        score: Score,
+        best_move: Option<Move>,
+        static_eval: Option<i32>,
+    ) {
+        let age = self.age;
+        let bucket = self.bucket(board);
+        let bucket = &mut self.buckets[bucket];
+
+        // Prefer the slot already holding this position, then an empty slot, and otherwise the
+        // shallowest node, treating every search it has survived as costing two plies of depth
+        let slot = bucket
+            .iter()
+            .position(|node| node.is_some_and(|node| node.hash == board.hash))
+            .or_else(|| bucket.iter().position(Option::is_none))
+            .unwrap_or_else(|| {
+                (0..BUCKET_SIZE)
+                    .min_by_key(|&slot| {
+                        let node = bucket[slot].expect("full buckets only contain nodes");
+                        node.depth as isize - 2 * age.wrapping_sub(node.age) as isize
+                    })
+                    .expect("buckets are never empty")
+            });
+
+        // Keep what is already known about the position when the new search didn't find it
+        let previous = bucket[slot].filter(|node| node.hash == board.hash);
+
+        // A deeper bound from this search is worth more than a shallower one, so only an exact
+        // score replaces it
+        if let Some(node) = &mut bucket[slot]
+            && node.hash == board.hash
+            && node.age == age
+            && node.depth > depth
+            && !matches!(score, Score::Exact(_))
+        {
+            node.best_move = node.best_move.or(best_move);
+            node.static_eval = node.static_eval.or(static_eval);
+            return;
+        }
+
+        bucket[slot] = Some(Node {
             hash: board.hash,
             depth,
-            score,
+            score: score.map(|score| to_tt(score, ply)),
+            best_move: best_move.or(previous.and_then(|node| node.best_move)),
+            static_eval: static_eval.or(previous.and_then(|node| node.static_eval)),
+            age,
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculated::values::MATE_VALUE;
+
+    #[test]
+    fn mate_scores_are_ply_relative() {
+        let mut tt = TranspositionTable::new(1);
+        let board = Board::default();
+
+        // Mating in 3 plies from a node at ply 2 is mating in 1 ply from the node itself
+        tt.insert(&board, 4, 2, Score::Exact(MATE_VALUE - 3), None, None);
+        assert_eq!(
+            tt.get(&board, 4, 6, -MATE_VALUE, MATE_VALUE),
+            Some(MATE_VALUE - 7)
+        );
+        assert_eq!(tt.get(&board, 5, 6, -MATE_VALUE, MATE_VALUE), None);
+    }
+
+    #[test]
+    fn keeps_best_move_and_eval() {
+        let mut tt = TranspositionTable::new(1);
+        let board = Board::default();
+        let mv = Move::coordinate("e2e4");
+
+        tt.insert(&board, 3, 0, Score::Beta(50), Some(mv), Some(20));
+        tt.insert(&board, 4, 0, Score::Alpha(10), None, None);
+
+        let node = tt.probe(&board, 0).unwrap();
+        assert_eq!(node.depth, 4);
+        assert_eq!(node.score, Score::Alpha(10));
+        assert_eq!(node.best_move, Some(mv));
+        assert_eq!(node.static_eval, Some(20));
+    }
+
+    #[test]
+    fn keeps_deeper_bounds() {
+        let mut tt = TranspositionTable::new(1);
+        let board = Board::default();
+        let mv = Move::coordinate("e2e4");
+
+        tt.insert(&board, 6, 0, Score::Beta(50), None, None);
+        tt.insert(&board, 2, 0, Score::Alpha(10), Some(mv), Some(20));
+        let node = tt.probe(&board, 0).unwrap();
+        assert_eq!((node.depth, node.score), (6, Score::Beta(50)));
+        assert_eq!(node.best_move, Some(mv));
+        assert_eq!(node.static_eval, Some(20));
+
+        // Exact scores always replace it
+        tt.insert(&board, 2, 0, Score::Exact(30), None, None);
+        let node = tt.probe(&board, 0).unwrap();
+        assert_eq!((node.depth, node.score), (2, Score::Exact(30)));
+
+        // As does anything once the node is from an earlier search
+        tt.insert(&board, 6, 0, Score::Beta(50), None, None);
+        tt.new_search();
+        tt.insert(&board, 2, 0, Score::Alpha(10), None, None);
+        let node = tt.probe(&board, 0).unwrap();
+        assert_eq!((node.depth, node.score), (2, Score::Alpha(10)));
+    }
+
+    #[test]
+    fn clear() {
+        let mut tt = TranspositionTable::new(1);
+        let board = Board::default();
+
+        tt.insert(&board, 1, 0, Score::Exact(0), None, None);
+        assert!(tt.probe(&board, 0).is_some());
+
+        tt.clear();
+        assert!(tt.probe(&board, 0).is_none());
+        assert_eq!(tt.hashfull(), 0);
+    }
+}
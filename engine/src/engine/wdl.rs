@@ -0,0 +1,85 @@
+//! Chances of winning, drawing and losing from a score, modelled on fixed node self-play games
+//!
+//! Each of winning and losing is a logistic curve in the score, centred on the score at which that
+//! result becomes as likely as not and spread out by a scale, with the rest of the chance given
+//! to a draw. Both depend on the material left on the board: with more pieces around a smaller
+//! advantage can be enough to win, but scores are less certain so the curve is flatter. The
+//! parameters were fitted by the `wdl` tool to 22,292 positions from 400 `datagen` games at 5,000
+//! nodes.
+
+use crate::engine::search::is_mate;
+use crate::repr::Board;
+use crate::repr::constants::*;
+
+/// Centre of the win curve in centipawns, at no material and per point of material
+const CENTRE: (f64, f64) = (318.7, -1.21);
+/// Scale of the win curve in centipawns, at no material and per point of material
+const SCALE: (f64, f64) = (108.5, 1.83);
+
+/// Material values for the model in points, indexed by piece type
+const MATERIAL_POINTS: [i32; 6] = [1, 3, 3, 5, 9, 0];
+/// Material in the starting position, beyond which the model wasn't fitted
+const MAX_MATERIAL: i32 = 78;
+
+/// Win, draw and loss chances per mille for the side to move, which always add up to 1000
+pub(super) fn wdl(score: i32, board: &Board) -> (u32, u32, u32) {
+    if is_mate(score) {
+        return if score > 0 {
+            (1000, 0, 0)
+        } else {
+            (0, 0, 1000)
+        };
+    }
+
+    let material = wdl_material(board) as f64;
+    let centre = CENTRE.0 + CENTRE.1 * material;
+    let scale = SCALE.0 + SCALE.1 * material;
+    let chance = |score: f64| (1000.0 / (1.0 + ((centre - score) / scale).exp())).round() as u32;
+
+    let score = score as f64;
+    let win = chance(score);
+    let loss = chance(-score);
+    (win, 1000 - win - loss, loss)
+}
+
+/// Total material of both sides in points, as the model measures it
+pub fn wdl_material(board: &Board) -> i32 {
+    let mut material = 0;
+    for colour in [WHITE, BLACK] {
+        for piece in [PAWN, KNIGHT, BISHOP, ROOK, QUEEN] {
+            material += MATERIAL_POINTS[piece as usize]
+                * board.pieces[colour as usize][piece as usize].count_ones() as i32;
+        }
+    }
+    material.min(MAX_MATERIAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculated::values::MATE_VALUE;
+
+    #[test]
+    fn chances() {
+        let start = Board::default();
+        let (win, draw, loss) = wdl(0, &start);
+        assert_eq!(win + draw + loss, 1000);
+        assert_eq!(win, loss);
+
+        // Better scores are more likely to win, and the chances always add up
+        let mut previous = 0;
+        for score in (-1_000..=1_000).step_by(50) {
+            let (win, draw, loss) = wdl(score, &start);
+            assert_eq!(win + draw + loss, 1000);
+            assert!(win >= previous);
+            previous = win;
+        }
+
+        // The same advantage is more decisive with less material left
+        let ending = Board::from_fen("8/5k2/8/3p4/8/2P5/3K4/8 w - - 0 1").unwrap();
+        assert!(wdl(600, &ending).0 > wdl(600, &start).0);
+
+        assert_eq!(wdl(MATE_VALUE - 3, &start), (1000, 0, 0));
+        assert_eq!(wdl(-MATE_VALUE + 2, &start), (0, 0, 1000));
+    }
+}
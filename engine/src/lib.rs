@@ -1,9 +1,19 @@
 #![feature(int_roundings)]
 
-pub use engine::Engine;
+pub use calculated::values::{SCALE_NORMAL, TOTAL_PHASE};
+pub use calculated::{between, line};
+pub use engine::{
+    DEFAULT_HASH_MB, Engine, EngineOptions, EvalParams, EvalParamsError, EvalTrace, GuiOption,
+    OptionType, OptionValue, Protocol, SearchLimits, SearchParams, SearchSignals, SearchThread,
+    Term, Tunable, allocate_time, registry, wdl_material,
+};
+pub use nnue::{Network, NetworkError};
 pub use repr::Board;
 pub use repr::Move;
+pub use repr::Pin;
+pub use repr::constants::{BLACK, WHITE};
 
 mod calculated;
 mod engine;
+mod nnue;
 mod repr;
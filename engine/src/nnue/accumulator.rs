@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use super::{HIDDEN, Network, feature};
+use crate::repr::constants::*;
+
+/// Hidden layer of the network from one side's perspective, before activation
+pub(crate) type Accumulator = [i16; HIDDEN];
+
+/// A piece on a square, as its colour, type and square
+pub(crate) type Placement = (u8, u8, u8);
+
+/// Network attached to a board along with the accumulators of both sides for the current position
+/// and every position before it, so that unmaking a move only has to drop the latest pair
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Nnue {
+    pub(crate) network: Arc<Network>,
+    stack: Vec<[Accumulator; 2]>,
+}
+
+impl Nnue {
+    pub(crate) fn new(network: Arc<Network>, squares: &[Option<(u8, u8)>; 64]) -> Self {
+        let mut accumulators = [network.feature_bias; 2];
+        for (square, piece) in squares.iter().enumerate() {
+            if let &Some((colour, piece)) = piece {
+                for perspective in [WHITE, BLACK] {
+                    let weights =
+                        &network.feature_weights[feature(perspective, colour, piece, square as u8)];
+                    add(&mut accumulators[perspective as usize], weights);
+                }
+            }
+        }
+
+        Self {
+            network,
+            stack: vec![accumulators],
+        }
+    }
+
+    /// Push the accumulators of the position reached by adding and removing the given pieces
+    pub(crate) fn push(
+        &mut self,
+        added: impl IntoIterator<Item = Placement> + Clone,
+        removed: impl IntoIterator<Item = Placement> + Clone,
+    ) {
+        let mut accumulators = *self.stack.last().expect("accumulator stack is never empty");
+
+        for perspective in [WHITE, BLACK] {
+            let accumulator = &mut accumulators[perspective as usize];
+            for (colour, piece, square) in added.clone() {
+                add(
+                    accumulator,
+                    &self.network.feature_weights[feature(perspective, colour, piece, square)],
+                );
+            }
+            for (colour, piece, square) in removed.clone() {
+                subtract(
+                    accumulator,
+                    &self.network.feature_weights[feature(perspective, colour, piece, square)],
+                );
+            }
+        }
+
+        self.stack.push(accumulators);
+    }
+
+    /// Drop the accumulators of the current position, returning whether those of the previous
+    /// position were known, which they aren't for moves made before the network was attached
+    pub(crate) fn pop(&mut self) -> bool {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Evaluation of the current position relative to the side to move
+    pub(crate) fn evaluate(&self, active_colour: u8) -> i32 {
+        let accumulators = self.stack.last().expect("accumulator stack is never empty");
+        self.network.evaluate(
+            &accumulators[active_colour as usize],
+            &accumulators[1 - active_colour as usize],
+        )
+    }
+
+    #[cfg(test)]
+    pub(crate) fn accumulators(&self) -> &[Accumulator; 2] {
+        self.stack.last().expect("accumulator stack is never empty")
+    }
+}
+
+/// Add a feature's weights, wrapping on overflow so that subtracting them again always restores
+/// the accumulator, which is then exact whenever the true sum fits in an `i16`
+fn add(accumulator: &mut Accumulator, weights: &[i16; HIDDEN]) {
+    for (value, weight) in accumulator.iter_mut().zip(weights) {
+        *value = value.wrapping_add(*weight);
+    }
+}
+
+fn subtract(accumulator: &mut Accumulator, weights: &[i16; HIDDEN]) {
+    for (value, weight) in accumulator.iter_mut().zip(weights) {
+        *value = value.wrapping_sub(*weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow_is_reversible() {
+        let mut accumulator = [i16::MAX - 1; HIDDEN];
+        let weights = [100; HIDDEN];
+        add(&mut accumulator, &weights);
+        subtract(&mut accumulator, &weights);
+        assert_eq!(accumulator, [i16::MAX - 1; HIDDEN]);
+    }
+}
@@ -0,0 +1,32 @@
+//! Efficiently updatable neural network evaluation, using a perspective network with one hidden
+//! layer whose inputs are every piece type of each colour on every square
+pub(crate) use accumulator::Nnue;
+pub use network::{Network, NetworkError};
+
+mod accumulator;
+mod network;
+mod simd;
+
+use crate::repr::constants::*;
+
+/// Number of input features, one for each colour, piece type and square
+pub const INPUTS: usize = 768;
+/// Number of neurons in the hidden layer, computed by each side's accumulator
+pub const HIDDEN: usize = 256;
+/// Quantisation of the feature transformer, whose activations are clipped to this
+const QA: i32 = 255;
+/// Quantisation of the output layer
+const QB: i32 = 64;
+/// Scale from the network's output to centipawns
+const SCALE: i32 = 400;
+
+/// Input feature of a piece from a side's perspective, which sees its own pieces first and the
+/// board from its own side, so that both perspectives share weights
+pub(crate) fn feature(perspective: u8, colour: u8, piece: u8, square: u8) -> usize {
+    let (side, square) = if perspective == WHITE {
+        (colour, square)
+    } else {
+        (1 - colour, square ^ 56)
+    };
+    side as usize * 384 + piece as usize * 64 + square as usize
+}
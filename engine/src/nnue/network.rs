@@ -0,0 +1,123 @@
+use std::fmt::Debug;
+use std::path::Path;
+
+use thiserror::Error;
+
+use super::accumulator::Accumulator;
+use super::{HIDDEN, INPUTS, QA, QB, SCALE, simd};
+
+#[derive(Error, Debug)]
+pub enum NetworkError {
+    #[error("failed to read network: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("network should be {expected} bytes but was {actual}")]
+    Size { expected: usize, actual: usize },
+}
+
+/// Quantised weights of a `INPUTS -> HIDDEN -> 1` perspective network
+#[derive(Clone, PartialEq, Eq)]
+pub struct Network {
+    pub(super) feature_weights: Box<[[i16; HIDDEN]]>,
+    pub(super) feature_bias: [i16; HIDDEN],
+    /// Weights of the side to move's hidden layer, then of the other side's
+    pub(super) output_weights: [[i16; HIDDEN]; 2],
+    pub(super) output_bias: i16,
+}
+
+impl Debug for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Network({INPUTS} -> {HIDDEN}x2 -> 1)")
+    }
+}
+
+impl Network {
+    /// Size in bytes of a serialised network, made up of little endian 16 bit integers for the
+    /// feature weights, feature biases, output weights and output bias in that order
+    pub const SIZE: usize = (INPUTS * HIDDEN + HIDDEN + 2 * HIDDEN + 1) * 2;
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NetworkError> {
+        if bytes.len() != Self::SIZE {
+            return Err(NetworkError::Size {
+                expected: Self::SIZE,
+                actual: bytes.len(),
+            });
+        }
+
+        let mut values = bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]));
+        let mut layer = || {
+            let mut neurons = [0; HIDDEN];
+            neurons.fill_with(|| values.next().expect("size was checked"));
+            neurons
+        };
+
+        let feature_weights = (0..INPUTS).map(|_| layer()).collect();
+        let feature_bias = layer();
+        let output_weights = [layer(), layer()];
+        let output_bias = values.next().expect("size was checked");
+
+        Ok(Self {
+            feature_weights,
+            feature_bias,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, NetworkError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.feature_weights
+            .iter()
+            .chain([&self.feature_bias])
+            .chain(&self.output_weights)
+            .flatten()
+            .chain([&self.output_bias])
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// Evaluation in centipawns for the side whose accumulator is `us`
+    pub(super) fn evaluate(&self, us: &Accumulator, them: &Accumulator) -> i32 {
+        let output = simd::crelu_dot(us, &self.output_weights[0]) as i64
+            + simd::crelu_dot(them, &self.output_weights[1]) as i64
+            + self.output_bias as i64;
+        (output * SCALE as i64 / (QA * QB) as i64) as i32
+    }
+}
+
+#[cfg(test)]
+impl Network {
+    /// A network with small random weights, for testing that its evaluation is computed correctly
+    pub(crate) fn random(seed: u64) -> Self {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let bytes: Vec<u8> = (0..Self::SIZE / 2)
+            .flat_map(|_| rng.random_range(-128i16..128).to_le_bytes())
+            .collect();
+        Self::from_bytes(&bytes).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialisation() {
+        let network = Network::random(0);
+        let bytes = network.to_bytes();
+        assert_eq!(bytes.len(), Network::SIZE);
+        assert_eq!(Network::from_bytes(&bytes).unwrap(), network);
+
+        assert!(matches!(
+            Network::from_bytes(&bytes[1..]),
+            Err(NetworkError::Size { .. })
+        ));
+    }
+}
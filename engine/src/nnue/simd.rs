@@ -0,0 +1,72 @@
+use super::{HIDDEN, QA};
+
+/// Sum of the clipped activations multiplied by their weights
+pub(super) fn crelu_dot(values: &[i16; HIDDEN], weights: &[i16; HIDDEN]) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2
+        return unsafe { crelu_dot_avx2(values, weights) };
+    }
+
+    crelu_dot_scalar(values, weights)
+}
+
+fn crelu_dot_scalar(values: &[i16; HIDDEN], weights: &[i16; HIDDEN]) -> i32 {
+    values
+        .iter()
+        .zip(weights)
+        .map(|(&value, &weight)| (value as i32).clamp(0, QA) * weight as i32)
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+fn crelu_dot_avx2(values: &[i16; HIDDEN], weights: &[i16; HIDDEN]) -> i32 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 16;
+
+    let zero = _mm256_setzero_si256();
+    let ceiling = _mm256_set1_epi16(QA as i16);
+    let mut sum = _mm256_setzero_si256();
+
+    for (values, weights) in values.chunks_exact(LANES).zip(weights.chunks_exact(LANES)) {
+        // SAFETY: each chunk holds exactly 16 i16s, and unaligned loads are allowed
+        let (values, weights) = unsafe {
+            (
+                _mm256_loadu_si256(values.as_ptr().cast()),
+                _mm256_loadu_si256(weights.as_ptr().cast()),
+            )
+        };
+        let clipped = _mm256_min_epi16(_mm256_max_epi16(values, zero), ceiling);
+        sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, weights));
+    }
+
+    let halves = _mm_add_epi32(
+        _mm256_castsi256_si128(sum),
+        _mm256_extracti128_si256::<1>(sum),
+    );
+    let pairs = _mm_add_epi32(halves, _mm_shuffle_epi32::<0b01_00_11_10>(halves));
+    let total = _mm_add_epi32(pairs, _mm_shuffle_epi32::<0b10_11_00_01>(pairs));
+    _mm_cvtsi128_si32(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let values = std::array::from_fn(|_| rng.random_range(-400..400));
+            let weights = std::array::from_fn(|_| rng.random_range(-200..200));
+            assert_eq!(
+                crelu_dot(&values, &weights),
+                crelu_dot_scalar(&values, &weights)
+            );
+        }
+    }
+}
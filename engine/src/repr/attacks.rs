@@ -0,0 +1,187 @@
+use super::bitboards::{A_FILE, H_FILE};
+use super::board::Board;
+use super::constants::*;
+use crate::calculated::{
+    KING_MOVES, between, generate_bishop_moves, generate_knight_moves, generate_rook_moves,
+};
+
+/// A piece pinned to its own king by an enemy slider
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pin {
+    pub square: u8,
+    pub pinner: u8,
+    /// Squares the pinned piece may still move to, up to and including the pinner
+    pub ray: u64,
+}
+
+impl Board {
+    /// Pieces of both colours attacking a square through the given occupancy, removing pieces
+    /// from the occupancy reveals any sliders x-raying through them
+    pub fn attackers_to(&self, square: u8, occupancy: u64) -> u64 {
+        let [white, black] = self.pieces;
+
+        let knights = white[KNIGHT as usize] | black[KNIGHT as usize];
+        let kings = white[KING as usize] | black[KING as usize];
+        let diagonal = white[BISHOP as usize]
+            | black[BISHOP as usize]
+            | white[QUEEN as usize]
+            | black[QUEEN as usize];
+        let orthogonal = white[ROOK as usize]
+            | black[ROOK as usize]
+            | white[QUEEN as usize]
+            | black[QUEEN as usize];
+
+        (self.pawn_attackers(square, WHITE)
+            | self.pawn_attackers(square, BLACK)
+            | generate_knight_moves(square) & knights
+            | KING_MOVES[square as usize] & kings
+            | generate_bishop_moves(square, occupancy) & diagonal
+            | generate_rook_moves(square, occupancy) & orthogonal)
+            & occupancy
+    }
+
+    /// Pieces of both colours attacking a square in the current position
+    pub fn attackers(&self, square: u8) -> u64 {
+        self.attackers_to(square, self.blockers())
+    }
+
+    /// Whether any piece of the given colour attacks a square, checking the cheapest pieces first
+    pub fn is_attacked(&self, square: u8, by: u8) -> bool {
+        let pieces = self.pieces[by as usize];
+
+        self.pawn_attackers(square, by) != 0
+            || generate_knight_moves(square) & pieces[KNIGHT as usize] != 0
+            || KING_MOVES[square as usize] & pieces[KING as usize] != 0
+            || generate_bishop_moves(square, self.blockers())
+                & (pieces[BISHOP as usize] | pieces[QUEEN as usize])
+                != 0
+            || generate_rook_moves(square, self.blockers())
+                & (pieces[ROOK as usize] | pieces[QUEEN as usize])
+                != 0
+    }
+
+    /// Enemy pieces giving check to the side to move
+    pub fn checkers(&self) -> u64 {
+        self.king_square(self.active_colour).map_or(0, |king| {
+            self.attackers(king) & self.colour_pieces(1 - self.active_colour)
+        })
+    }
+
+    /// Pieces of the given colour pinned to their king
+    pub fn pinned(&self, colour: u8) -> Vec<Pin> {
+        let Some(king) = self.king_square(colour) else {
+            return Vec::new();
+        };
+
+        let enemy = self.pieces[1 - colour as usize];
+        let snipers = generate_bishop_moves(king, 0)
+            & (enemy[BISHOP as usize] | enemy[QUEEN as usize])
+            | generate_rook_moves(king, 0) & (enemy[ROOK as usize] | enemy[QUEEN as usize]);
+
+        let mut pins = Vec::new();
+        let mut remaining = snipers;
+        while remaining != 0 {
+            let pinner = remaining.trailing_zeros() as u8;
+            remaining &= remaining - 1;
+
+            let blockers = between(king, pinner) & self.blockers();
+            if blockers.count_ones() == 1 && blockers & self.colour_pieces(colour) != 0 {
+                pins.push(Pin {
+                    square: blockers.trailing_zeros() as u8,
+                    pinner,
+                    ray: between(king, pinner) | 1 << pinner,
+                });
+            }
+        }
+        pins
+    }
+
+    pub(crate) fn colour_pieces(&self, colour: u8) -> u64 {
+        self.pieces[colour as usize]
+            .into_iter()
+            .reduce(|acc, e| acc | e)
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn king_square(&self, colour: u8) -> Option<u8> {
+        let king = self.pieces[colour as usize][KING as usize];
+        (king != 0).then(|| king.trailing_zeros() as u8)
+    }
+
+    /// Pawns of the given colour attacking a square
+    fn pawn_attackers(&self, square: u8, colour: u8) -> u64 {
+        let target = 1u64 << square;
+        let origins = if colour == WHITE {
+            (target >> 7) & !A_FILE | (target >> 9) & !H_FILE
+        } else {
+            (target << 7) & !H_FILE | (target << 9) & !A_FILE
+        };
+        origins & self.pieces[colour as usize][PAWN as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attackers() {
+        let board = Board::from_fen("4k3/8/3p4/4n3/3P4/5N2/8/4R1K1 w - - 0 1").unwrap();
+        assert_eq!(board.attackers(E5), 1 << D4 | 1 << D6 | 1 << F3 | 1 << E1);
+        assert_eq!(
+            board.attackers_to(E5, board.blockers() ^ 1 << E1),
+            1 << D4 | 1 << D6 | 1 << F3
+        );
+    }
+
+    #[test]
+    fn is_attacked() {
+        let board = Board::from_fen("4k3/8/3p4/4n3/3P4/5N2/8/4R1K1 w - - 0 1").unwrap();
+        assert!(board.is_attacked(E5, WHITE));
+        assert!(board.is_attacked(E5, BLACK));
+        assert!(board.is_attacked(E3, WHITE));
+        assert!(!board.is_attacked(E6, WHITE));
+        assert!(!board.is_attacked(A8, WHITE));
+        assert!(!board.is_attacked(H1, BLACK));
+    }
+
+    #[test]
+    fn checkers() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4r3/4K2N w - - 0 1").unwrap();
+        assert_eq!(board.checkers(), 1 << E2);
+
+        let board = Board::from_fen("4k3/8/8/8/1b6/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.checkers(), 1 << B4);
+
+        assert_eq!(Board::default().checkers(), 0);
+    }
+
+    #[test]
+    fn pins() {
+        let board = Board::from_fen("4k3/4r3/8/b7/8/2N5/4R3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.pinned(WHITE),
+            vec![
+                Pin {
+                    square: C3,
+                    pinner: A5,
+                    ray: 1 << D2 | 1 << C3 | 1 << B4 | 1 << A5,
+                },
+                Pin {
+                    square: E2,
+                    pinner: E7,
+                    ray: 0x0010_1010_1010_1000,
+                },
+            ]
+        );
+        assert_eq!(
+            board.pinned(BLACK),
+            vec![Pin {
+                square: E7,
+                pinner: E2,
+                ray: 0x0010_1010_1010_1000,
+            }]
+        );
+        assert_eq!(Board::default().pinned(WHITE), vec![]);
+    }
+}
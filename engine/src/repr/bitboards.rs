@@ -1,5 +1,7 @@
 //! Utility functions for working with bitboards
 
+use super::constants::{BLACK, WHITE};
+
 // Files
 pub const A_FILE: u64 = 0x0101010101010101;
 pub const B_FILE: u64 = 0x0202020202020202;
@@ -12,6 +14,9 @@ pub const SECOND_RANK: u64 = 0xff00;
 pub const SEVENTH_RANK: u64 = 0xff000000000000;
 pub const EIGHTH_RANK: u64 = 0xff00000000000000;
 
+// Squares
+pub const DARK_SQUARES: u64 = 0xaa55aa55aa55aa55;
+
 /// Filter out certain squares from a bitboard
 pub fn filter(bitboard: u64, filters: Vec<u64>) -> u64 {
     bitboard & !(filters.into_iter().reduce(|acc, e| acc | e).unwrap())
@@ -31,3 +36,54 @@ pub fn print_bitboard(bitboard: u64) {
     }
     println!("  a b c d e f g h");
 }
+
+/// Every square on the same file as a square
+pub fn file_mask(square: u8) -> u64 {
+    A_FILE << (square % 8)
+}
+
+/// Every square on the same rank as a square
+pub fn rank_mask(square: u8) -> u64 {
+    FIRST_RANK << (square / 8 * 8)
+}
+
+/// Every square on the files either side of a square
+pub fn adjacent_files(square: u8) -> u64 {
+    let file = file_mask(square);
+    (file << 1 & !A_FILE) | (file >> 1 & !H_FILE)
+}
+
+/// Every square on the ranks strictly in front of a square from the given colour's point of view
+pub fn forward_ranks(colour: u8, square: u8) -> u64 {
+    let rank = square / 8;
+    if colour == WHITE {
+        u64::MAX.checked_shl(8 * (rank as u32 + 1)).unwrap_or(0)
+    } else {
+        u64::MAX.checked_shr(8 * (8 - rank as u32)).unwrap_or(0)
+    }
+}
+
+/// Number of king moves between two squares on an empty board
+pub fn distance(a: u8, b: u8) -> i32 {
+    let files = (a % 8).abs_diff(b % 8);
+    let ranks = (a / 8).abs_diff(b / 8);
+    files.max(ranks) as i32
+}
+
+/// Rank of a square counted from the given colour's side of the board
+pub fn relative_rank(colour: u8, square: u8) -> usize {
+    if colour == WHITE {
+        square as usize / 8
+    } else {
+        7 - square as usize / 8
+    }
+}
+
+/// Squares attacked by a set of pawns of the given colour
+pub fn pawn_attacks(colour: u8, pawns: u64) -> u64 {
+    if colour == BLACK {
+        (pawns >> 7 & !A_FILE) | (pawns >> 9 & !H_FILE)
+    } else {
+        (pawns << 7 & !H_FILE) | (pawns << 9 & !A_FILE)
+    }
+}
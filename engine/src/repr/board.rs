@@ -1,17 +1,21 @@
 use std::fmt::Display;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use bitvec::prelude::*;
 use thiserror::Error;
 
+use super::bitboards::{EIGHTH_RANK, FIRST_RANK};
 use super::constants::*;
 use super::piece_move::{HistoryMove, Move};
 use super::square::Square;
 use super::zobrist::*;
+use crate::calculated::values::{PHASE_WEIGHTS, TOTAL_PHASE};
 use crate::calculated::{
     KING_MOVES, PAWN_ATTACKS, generate_bishop_moves, generate_king_moves, generate_knight_moves,
     generate_pawn_moves, generate_rook_moves,
 };
+use crate::nnue::{Network, Nnue};
 
 #[derive(Error, Debug)]
 #[error("poorly formatted fen string")]
@@ -29,6 +33,12 @@ pub struct Board {
     full_moves: u8,
     history: Vec<HistoryMove>,
     pub hash: u64,
+    /// Zobrist hash of only the pawns, for caching pawn structure evaluation
+    pub pawn_hash: u64,
+    /// Sum of the phase weights of the pieces on the board
+    phase: i32,
+    /// Network evaluating the position, with accumulators kept up to date as moves are made
+    nnue: Option<Nnue>,
 }
 
 impl Default for Board {
@@ -144,29 +154,175 @@ impl Board {
             full_moves,
             history: Vec::new(),
             hash: instantiate_board_hash(active_colour, squares, castling, en_passant),
+            pawn_hash: instantiate_pawn_hash(squares),
+            phase: squares
+                .iter()
+                .flatten()
+                .map(|&(_, piece)| PHASE_WEIGHTS[piece as usize])
+                .sum(),
+            nnue: None,
         })
     }
 
+    pub fn fen(&self) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let Some((colour, piece)) = self.squares[rank * 8 + file] else {
+                    empty += 1;
+                    continue;
+                };
+
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+
+                let kind = match piece {
+                    KING => 'k',
+                    QUEEN => 'q',
+                    ROOK => 'r',
+                    BISHOP => 'b',
+                    KNIGHT => 'n',
+                    PAWN => 'p',
+                    p => panic!("unexpected piece constant: {p}"),
+                };
+                fen.push(if colour == WHITE {
+                    kind.to_ascii_uppercase()
+                } else {
+                    kind
+                });
+            }
+
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        let colour = if self.active_colour == WHITE {
+            'w'
+        } else {
+            'b'
+        };
+
+        let castling: String = [
+            (WHITE_KING_SIDE, 'K'),
+            (WHITE_QUEEN_SIDE, 'Q'),
+            (BLACK_KING_SIDE, 'k'),
+            (BLACK_QUEEN_SIDE, 'q'),
+        ]
+        .into_iter()
+        .filter(|(right, _)| self.castling & right != 0)
+        .map(|(_, ch)| ch)
+        .collect();
+        let castling = if castling.is_empty() {
+            "-".to_string()
+        } else {
+            castling
+        };
+
+        let en_passant = self
+            .en_passant
+            .map_or("-".to_string(), |square| Square(square).to_string());
+
+        format!(
+            "{fen} {colour} {castling} {en_passant} {} {}",
+            self.half_moves, self.full_moves
+        )
+    }
+
+    /// The same position with the board flipped vertically and the colours swapped, so that the
+    /// side to move and every evaluation term change sides
+    pub fn mirror(&self) -> Self {
+        let mut pieces = [[0; 6]; 2];
+        let mut squares = [None; 64];
+
+        for (square, piece) in self.squares.iter().enumerate() {
+            if let &Some((colour, piece)) = piece {
+                let square = square ^ 56;
+                pieces[1 - colour as usize][piece as usize] |= 1 << square;
+                squares[square] = Some((1 - colour, piece));
+            }
+        }
+
+        let active_colour = 1 - self.active_colour;
+        let castling = (self.castling & (WHITE_KING_SIDE | WHITE_QUEEN_SIDE)) << 2
+            | (self.castling & (BLACK_KING_SIDE | BLACK_QUEEN_SIDE)) >> 2;
+        let en_passant = self.en_passant.map(|square| square ^ 56);
+
+        Board {
+            pieces,
+            squares,
+            active_colour,
+            castling,
+            en_passant,
+            half_moves: self.half_moves,
+            full_moves: self.full_moves,
+            history: Vec::new(),
+            hash: instantiate_board_hash(active_colour, squares, castling, en_passant),
+            pawn_hash: instantiate_pawn_hash(squares),
+            phase: self.phase,
+            nnue: self
+                .nnue
+                .as_ref()
+                .map(|nnue| Nnue::new(nnue.network.clone(), &squares)),
+        }
+    }
+
     pub fn moves(&mut self) -> Vec<Move> {
+        self.generate_moves(|_| u64::MAX)
+    }
+
+    /// Legal captures, including en passant, and promotions to a queen, as searched in quiescence
+    pub fn captures(&mut self) -> Vec<Move> {
+        let colour = self.active_colour;
+        let opponent_pieces = self.colour_pieces(1 - colour);
+        let en_passant = self.en_passant.map_or(0, |s| 1 << s);
+
+        let mut captures = self.generate_moves(|piece| {
+            if piece == PAWN {
+                opponent_pieces | en_passant | FIRST_RANK | EIGHTH_RANK
+            } else {
+                opponent_pieces
+            }
+        });
+        captures.retain(|mv| mv.promotion.is_none_or(|piece| piece == QUEEN));
+        captures
+    }
+
+    /// Generate legal moves for the side to move, restricted to the target squares for each piece
+    fn generate_moves(&mut self, targets: impl Fn(u8) -> u64) -> Vec<Move> {
         let mut moves = vec![];
         for square in A1..=H8 {
             if let Some((colour, piece)) = self.squares[square as usize]
                 && colour == self.active_colour
             {
-                self.get_moves_for_square(square, colour, piece, &mut moves);
+                self.get_moves_for_square(square, colour, piece, targets(piece), &mut moves);
             }
         }
         moves
     }
 
-    fn get_moves_for_square(&mut self, square: u8, colour: u8, piece: u8, moves: &mut Vec<Move>) {
-        for candidate_move in self.generate_pseudo_moves(square, colour, piece) {
+    fn get_moves_for_square(
+        &mut self,
+        square: u8,
+        colour: u8,
+        piece: u8,
+        targets: u64,
+        moves: &mut Vec<Move>,
+    ) {
+        for candidate_move in self.generate_pseudo_moves(square, colour, piece, targets) {
             self.make_move(&candidate_move);
 
             // Only add move if it doesn't result in our king being attacked
-            if self.attacked(self.active_colour)
-                & self.pieces[1 - self.active_colour as usize][KING as usize]
-                == 0
+            if !self
+                .king_square(colour)
+                .is_some_and(|king| self.is_attacked(king, self.active_colour))
             {
                 moves.push(candidate_move);
             }
@@ -180,6 +336,7 @@ impl Board {
         square: u8,
         colour: u8,
         piece: u8,
+        targets: u64,
     ) -> impl Iterator<Item = Move> + use<> {
         let friendly_pieces = self.pieces[colour as usize]
             .into_iter()
@@ -214,7 +371,8 @@ impl Board {
             }
             ROOK => generate_rook_moves(square, blockers),
             _ => panic!("unknown piece"),
-        } & !friendly_pieces;
+        } & !friendly_pieces
+            & targets;
 
         pseudo_moves
             .view_bits::<Lsb0>()
@@ -224,8 +382,8 @@ impl Board {
             .filter(|(_, m)| *m)
             .flat_map(move |(destination, _)| {
                 if piece == PAWN
-                    && ((colour == WHITE && (A7..H7).contains(&square))
-                        || (colour == BLACK && (A2..H2).contains(&square)))
+                    && ((colour == WHITE && (A7..=H7).contains(&square))
+                        || (colour == BLACK && (A2..=H2).contains(&square)))
                 {
                     vec![
                         Move::promotion(square, destination as u8, BISHOP),
@@ -243,6 +401,7 @@ impl Board {
         // Data needed for saving history move
         let prev_castling = self.castling;
         let prev_en_passant = self.en_passant;
+        let prev_half_moves = self.half_moves;
         let mut captured_en_passant = false;
 
         // Pieces
@@ -258,11 +417,15 @@ impl Board {
                     self.squares[mv.destination.0 as usize - 8] = None;
                     self.hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize - 8][BLACK as usize]
                         [PAWN as usize];
+                    self.pawn_hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize - 8]
+                        [BLACK as usize][PAWN as usize];
                 } else {
                     self.pieces[WHITE as usize][PAWN as usize] ^= 1 << (mv.destination.0 + 8);
                     self.squares[mv.destination.0 as usize + 8] = None;
                     self.hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize + 8][WHITE as usize]
                         [PAWN as usize];
+                    self.pawn_hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize + 8]
+                        [WHITE as usize][PAWN as usize];
                 }
                 captured_en_passant = true;
             }
@@ -374,9 +537,11 @@ impl Board {
             self.pieces[colour as usize][promotion as usize] ^= 1 << mv.destination.0;
             self.squares[mv.source.0 as usize] = None;
             self.hash ^= ZOBRIST_SQUARES[mv.source.0 as usize][colour as usize][piece as usize];
+            self.pawn_hash ^= ZOBRIST_SQUARES[mv.source.0 as usize][colour as usize][PAWN as usize];
             self.squares[mv.destination.0 as usize] = Some((colour, promotion));
             self.hash ^=
                 ZOBRIST_SQUARES[mv.destination.0 as usize][colour as usize][promotion as usize];
+            self.phase += PHASE_WEIGHTS[promotion as usize];
         } else {
             // Normal move
             self.pieces[colour as usize][piece as usize] ^=
@@ -386,6 +551,11 @@ impl Board {
             self.squares[mv.destination.0 as usize] = Some((colour, piece));
             self.hash ^=
                 ZOBRIST_SQUARES[mv.destination.0 as usize][colour as usize][piece as usize];
+            if piece == PAWN {
+                self.pawn_hash ^= ZOBRIST_SQUARES[mv.source.0 as usize][colour as usize]
+                    [PAWN as usize]
+                    ^ ZOBRIST_SQUARES[mv.destination.0 as usize][colour as usize][PAWN as usize];
+            }
         }
 
         // Capture
@@ -393,6 +563,11 @@ impl Board {
             self.pieces[captured.0 as usize][captured.1 as usize] ^= 1 << mv.destination.0;
             self.hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize][captured.0 as usize]
                 [captured.1 as usize];
+            if captured.1 == PAWN {
+                self.pawn_hash ^=
+                    ZOBRIST_SQUARES[mv.destination.0 as usize][captured.0 as usize][PAWN as usize];
+            }
+            self.phase -= PHASE_WEIGHTS[captured.1 as usize];
         };
 
         // Switch colour
@@ -419,8 +594,51 @@ impl Board {
             en_passant_capture: captured_en_passant,
             removed_castling_rights: self.castling ^ prev_castling,
             previous_full_moves: self.full_moves,
-            previous_half_moves: self.half_moves,
+            previous_half_moves: prev_half_moves,
         });
+
+        if self.nnue.is_some() {
+            self.update_nnue();
+        }
+    }
+
+    /// Bring the accumulators up to date with the most recent move
+    fn update_nnue(&mut self) {
+        let Some(nnue) = &mut self.nnue else {
+            return;
+        };
+        let mv = self.history.last().expect("a move was just made");
+        let (colour, piece) = mv.moved;
+        let (source, destination) = (mv.source.0, mv.destination.0);
+
+        let mut added = [
+            Some((colour, mv.promotion.unwrap_or(piece), destination)),
+            None,
+        ];
+        let mut removed = [Some((colour, piece, source)), None];
+
+        if let Some((captured_colour, captured)) = mv.captured {
+            removed[1] = Some((captured_colour, captured, destination));
+        } else if mv.en_passant_capture {
+            let square = if colour == WHITE {
+                destination - 8
+            } else {
+                destination + 8
+            };
+            removed[1] = Some((1 - colour, PAWN, square));
+        } else if piece == KING && source.abs_diff(destination) == 2 {
+            let (from, to) = match destination {
+                G1 => (H1, F1),
+                C1 => (A1, D1),
+                G8 => (H8, F8),
+                C8 => (A8, D8),
+                s => panic!("can't castle to square: {}", Square(s)),
+            };
+            added[1] = Some((colour, ROOK, to));
+            removed[1] = Some((colour, ROOK, from));
+        }
+
+        nnue.push(added.into_iter().flatten(), removed.into_iter().flatten());
     }
 
     pub fn unmake_move(&mut self) {
@@ -442,11 +660,15 @@ impl Board {
                     self.squares[mv.destination.0 as usize - 8] = Some((BLACK, PAWN));
                     self.hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize - 8][BLACK as usize]
                         [PAWN as usize];
+                    self.pawn_hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize - 8]
+                        [BLACK as usize][PAWN as usize];
                 } else {
                     self.pieces[WHITE as usize][PAWN as usize] ^= 1 << (mv.destination.0 + 8);
                     self.squares[mv.destination.0 as usize + 8] = Some((WHITE, PAWN));
                     self.hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize + 8][WHITE as usize]
                         [PAWN as usize];
+                    self.pawn_hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize + 8]
+                        [WHITE as usize][PAWN as usize];
                 }
             }
         } else if piece == KING {
@@ -508,8 +730,10 @@ impl Board {
             self.squares[mv.destination.0 as usize] = None;
             self.hash ^= ZOBRIST_SQUARES[mv.source.0 as usize][colour as usize][piece as usize];
             self.squares[mv.source.0 as usize] = Some((colour, PAWN));
+            self.pawn_hash ^= ZOBRIST_SQUARES[mv.source.0 as usize][colour as usize][PAWN as usize];
             self.hash ^=
                 ZOBRIST_SQUARES[mv.destination.0 as usize][colour as usize][promotion as usize];
+            self.phase -= PHASE_WEIGHTS[promotion as usize];
         } else {
             // Normal move
             self.pieces[colour as usize][piece as usize] ^=
@@ -519,6 +743,11 @@ impl Board {
                 ZOBRIST_SQUARES[mv.destination.0 as usize][colour as usize][piece as usize];
             self.squares[mv.source.0 as usize] = Some((colour, piece));
             self.hash ^= ZOBRIST_SQUARES[mv.source.0 as usize][colour as usize][piece as usize];
+            if piece == PAWN {
+                self.pawn_hash ^= ZOBRIST_SQUARES[mv.source.0 as usize][colour as usize]
+                    [PAWN as usize]
+                    ^ ZOBRIST_SQUARES[mv.destination.0 as usize][colour as usize][PAWN as usize];
+            }
         }
 
         // Capture
@@ -527,6 +756,11 @@ impl Board {
             self.squares[mv.destination.0 as usize] = Some((captured.0, captured.1));
             self.hash ^= ZOBRIST_SQUARES[mv.destination.0 as usize][captured.0 as usize]
                 [captured.1 as usize];
+            if captured.1 == PAWN {
+                self.pawn_hash ^=
+                    ZOBRIST_SQUARES[mv.destination.0 as usize][captured.0 as usize][PAWN as usize];
+            }
+            self.phase += PHASE_WEIGHTS[captured.1 as usize];
         } else {
             self.squares[mv.destination.0 as usize] = None;
         }
@@ -549,6 +783,53 @@ impl Board {
 
         // Reset counter for 50-move rule
         self.half_moves = mv.previous_half_moves;
+
+        if let Some(nnue) = &mut self.nnue
+            && !nnue.pop()
+        {
+            *nnue = Nnue::new(nnue.network.clone(), &self.squares);
+        }
+    }
+
+    /// Evaluate positions with the given network from now on, or stop if there is none
+    pub fn set_network(&mut self, network: Option<Arc<Network>>) {
+        self.nnue = network.map(|network| Nnue::new(network, &self.squares));
+    }
+
+    /// Evaluation of the position by the attached network relative to the side to move
+    pub fn evaluate_nnue(&self) -> Option<i32> {
+        self.nnue
+            .as_ref()
+            .map(|nnue| nnue.evaluate(self.active_colour))
+    }
+
+    /// Game phase from `TOTAL_PHASE` with all minor and major pieces on the board, down to zero
+    /// with only kings and pawns left
+    pub fn phase(&self) -> i32 {
+        self.phase.min(TOTAL_PHASE)
+    }
+
+    /// Number of moves made since the position was set up, which are all that can be unmade
+    pub fn moves_played(&self) -> usize {
+        self.history.len()
+    }
+
+    /// A move made the given number of plies ago, where one is the most recent move
+    pub(crate) fn previous_move(&self, plies_ago: usize) -> Option<&HistoryMove> {
+        self.history.iter().rev().nth(plies_ago.checked_sub(1)?)
+    }
+
+    /// Type of the piece captured by a move, including pawns captured en passant
+    pub fn captured_piece(&self, mv: &Move) -> Option<u8> {
+        match self.squares[mv.destination.0 as usize] {
+            Some((_, victim)) => Some(victim),
+            None if self.squares[mv.source.0 as usize].is_some_and(|(_, piece)| piece == PAWN)
+                && mv.source.0 % 8 != mv.destination.0 % 8 =>
+            {
+                Some(PAWN)
+            }
+            None => None,
+        }
     }
 
     pub fn attacked(&self, attacking_colour: u8) -> u64 {
@@ -572,11 +853,11 @@ impl Board {
     }
 
     pub fn in_check(&self) -> bool {
-        let attacked = self.attacked(1 - self.active_colour);
-        self.pieces[self.active_colour as usize][KING as usize] & attacked != 0
+        self.king_square(self.active_colour)
+            .is_some_and(|king| self.is_attacked(king, 1 - self.active_colour))
     }
 
-    fn blockers(&self) -> u64 {
+    pub(crate) fn blockers(&self) -> u64 {
         self.pieces
             .into_iter()
             .flatten()
@@ -585,8 +866,11 @@ impl Board {
     }
 
     pub fn perft(&mut self, depth: usize) -> usize {
-        let moves = self.moves();
+        if depth == 0 {
+            return 1;
+        }
 
+        let moves = self.moves();
         if depth == 1 {
             moves.len()
         } else {
@@ -658,6 +942,18 @@ fn instantiate_board_hash(
     hash
 }
 
+fn instantiate_pawn_hash(squares: [Option<(u8, u8)>; 64]) -> u64 {
+    let mut hash = 0u64;
+
+    for square in A1..=H8 {
+        if let Some((colour, PAWN)) = squares[square as usize] {
+            hash ^= ZOBRIST_SQUARES[square as usize][colour as usize][PAWN as usize];
+        }
+    }
+
+    hash
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -709,5 +1005,205 @@ mod tests {
         assert_eq!(bug_finder.perft(1), 44);
         assert_eq!(bug_finder.perft(2), 1486);
         assert_eq!(bug_finder.perft(3), 62_379);
+
+        // Promotions on both edge files, the well known promotion position mirrored so that
+        // pawns on the h-file promote as well
+        let mut promotions = Board::from_fen("5n1n/4kPPP/8/8/8/8/pppK4/N1N5 b - - 0 1").unwrap();
+        assert_eq!(promotions.perft(1), 24);
+        assert_eq!(promotions.perft(2), 496);
+        assert_eq!(promotions.perft(3), 9_483);
+    }
+
+    #[test]
+    fn h_file_promotions() {
+        let mut board = Board::from_fen("k7/7P/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let moves: Vec<String> = board.moves().iter().map(Move::to_string).collect();
+        for promotion in ["h7h8q", "h7h8r", "h7h8b", "h7h8n"] {
+            assert!(moves.contains(&promotion.to_string()));
+        }
+        assert!(!moves.contains(&"h7h8".to_string()));
+    }
+
+    #[test]
+    fn pawn_hash() {
+        let mut board =
+            Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+                .unwrap();
+        for mv in board.moves() {
+            board.make_move(&mv);
+            for reply in board.moves() {
+                board.make_move(&reply);
+                assert_eq!(board.pawn_hash, instantiate_pawn_hash(board.squares));
+                board.unmake_move();
+            }
+            assert_eq!(board.pawn_hash, instantiate_pawn_hash(board.squares));
+            board.unmake_move();
+        }
+
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        board.make_move(&Move::coordinate("e5d6"));
+        assert_eq!(board.pawn_hash, instantiate_pawn_hash(board.squares));
+
+        let before = Board::default().pawn_hash;
+        let mut board = Board::default();
+        board.make_move(&Move::coordinate("g1f3"));
+        assert_eq!(board.pawn_hash, before);
+    }
+
+    #[test]
+    fn nnue_accumulators() {
+        let network = Arc::new(Network::random(0));
+        let refreshed = |board: &Board| {
+            Nnue::new(network.clone(), &board.squares)
+                .accumulators()
+                .to_owned()
+        };
+
+        let mut board =
+            Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+                .unwrap();
+        board.set_network(Some(network.clone()));
+        for mv in board.moves() {
+            board.make_move(&mv);
+            for reply in board.moves() {
+                board.make_move(&reply);
+                assert_eq!(
+                    board.nnue.as_ref().unwrap().accumulators(),
+                    &refreshed(&board)
+                );
+                board.unmake_move();
+            }
+            board.unmake_move();
+            assert_eq!(
+                board.nnue.as_ref().unwrap().accumulators(),
+                &refreshed(&board)
+            );
+        }
+
+        // Castling and en passant
+        let mut board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        board.set_network(Some(network.clone()));
+        for mv in ["e1g1", "a6e2", "a2a4", "b4a3", "f3f6"] {
+            board.make_move(&Move::coordinate(mv));
+            assert_eq!(
+                board.nnue.as_ref().unwrap().accumulators(),
+                &refreshed(&board)
+            );
+        }
+    }
+
+    #[test]
+    fn nnue_symmetry() {
+        let network = Arc::new(Network::random(1));
+        let mut board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        board.set_network(Some(network));
+        assert_eq!(board.mirror().evaluate_nnue(), board.evaluate_nnue());
+
+        // Moves made before the network was attached can still be unmade
+        let mut board = Board::default();
+        board.make_move(&Move::coordinate("e2e4"));
+        board.set_network(Some(Arc::new(Network::random(2))));
+        board.unmake_move();
+        let mut fresh = Board::default();
+        fresh.set_network(Some(Arc::new(Network::random(2))));
+        assert_eq!(board.evaluate_nnue(), fresh.evaluate_nnue());
+    }
+
+    #[test]
+    fn fen() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/R3K3 w Q d6 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R b KQ - 1 8",
+        ] {
+            assert_eq!(Board::from_fen(fen).unwrap().fen(), fen);
+        }
+    }
+
+    #[test]
+    fn half_moves() {
+        let fen = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 5 8";
+        let mut board = Board::from_fen(fen).unwrap();
+        for mv in board.moves() {
+            board.make_move(&mv);
+            board.unmake_move();
+            assert_eq!(board.fen(), fen);
+        }
+    }
+
+    #[test]
+    fn mirror() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(board.mirror().mirror(), board);
+
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/R3K3 w Q d6 0 1").unwrap();
+        let mirrored = Board::from_fen("r3k3/8/8/8/3Pp3/8/8/4K3 b q d3 0 1").unwrap();
+        assert_eq!(board.mirror(), mirrored);
+
+        let mut opening =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap()
+                .mirror();
+        assert_eq!(opening.perft(3), 97_862);
+    }
+
+    #[test]
+    fn phase() {
+        let board = Board::default();
+        assert_eq!(board.phase(), TOTAL_PHASE);
+
+        let mut board = Board::from_fen("3qk3/2P5/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.phase(), 4);
+        board.make_move(&Move::coordinate("c7d8n"));
+        assert_eq!(board.phase(), 1);
+        board.unmake_move();
+        assert_eq!(board.phase(), 4);
+
+        let mut board =
+            Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+                .unwrap();
+        let phase = board.phase();
+        for mv in board.moves() {
+            board.make_move(&mv);
+            for reply in board.moves() {
+                board.make_move(&reply);
+                board.unmake_move();
+            }
+            board.unmake_move();
+            assert_eq!(board.phase(), phase);
+        }
+    }
+
+    #[test]
+    fn promotions() {
+        let mut board = Board::from_fen("4k3/7P/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.moves().len(), 9);
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/p7/4K3 b - - 0 1").unwrap();
+        assert_eq!(board.moves().len(), 9);
+    }
+
+    #[test]
+    fn captures() {
+        let mut board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let captures = board.captures();
+        assert_eq!(captures.len(), 8);
+        assert!(captures.iter().all(|mv| board.captured_piece(mv).is_some()));
+
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(board.captures(), vec![Move::coordinate("e5d6")]);
+        assert_eq!(board.captured_piece(&Move::coordinate("e5d6")), Some(PAWN));
+
+        let mut board = Board::from_fen("4k3/7P/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.captures(), vec![Move::coordinate("h7h8q")]);
     }
 }
@@ -1,11 +1,14 @@
 //! Data structures and methods for representing and manipulating a chess position
+pub use attacks::Pin;
 pub use board::Board;
 pub use piece_move::Move;
 
 pub mod bitboards;
 pub mod constants;
 
+mod attacks;
 mod board;
 mod piece_move;
+mod see;
 mod square;
 mod zobrist;
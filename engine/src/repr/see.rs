@@ -0,0 +1,139 @@
+use super::board::Board;
+use super::constants::*;
+use super::piece_move::Move;
+use crate::calculated::values::PIECE_VALUES;
+
+impl Board {
+    /// Static exchange evaluation: the material balance after both sides alternately recapture on
+    /// the move's destination with their least valuable attacker, either side stopping when it
+    /// would lose out by continuing. Pawns recapturing on the last rank promote to a queen, and
+    /// pins are not taken into account.
+    pub fn see(&self, mv: &Move) -> i32 {
+        let target = mv.destination.0;
+        let (colour, mut on_target) =
+            self.squares[mv.source.0 as usize].expect("valid moves always have a piece at source");
+
+        let mut occupancy = self.blockers() ^ 1 << mv.source.0;
+        let mut gain = [0; 32];
+
+        gain[0] = self
+            .captured_piece(mv)
+            .map_or(0, |victim| PIECE_VALUES[victim as usize]);
+        if self.squares[target as usize].is_none() && gain[0] != 0 {
+            // En passant, the captured pawn is behind the target square
+            let captured = if colour == WHITE {
+                target - 8
+            } else {
+                target + 8
+            };
+            occupancy ^= 1 << captured;
+        }
+        if let Some(promotion) = mv.promotion {
+            gain[0] += PIECE_VALUES[promotion as usize] - PIECE_VALUES[PAWN as usize];
+            on_target = promotion;
+        }
+
+        // Each entry is the balance for the side making that capture, if it were the last one
+        let mut side = 1 - colour;
+        let mut depth = 0;
+        while depth < gain.len() - 1 {
+            let attackers = self.attackers_to(target, occupancy);
+            let Some((square, mut piece)) = self.least_valuable_attacker(attackers, side) else {
+                break;
+            };
+
+            // The king can only recapture when the opponent has nothing left defending
+            if piece == KING
+                && attackers & occupancy & !(1 << square) & self.colour_pieces(1 - side) != 0
+            {
+                break;
+            }
+
+            depth += 1;
+            gain[depth] = PIECE_VALUES[on_target as usize] - gain[depth - 1];
+            if piece == PAWN && !(A2..=H7).contains(&target) {
+                gain[depth] += PIECE_VALUES[QUEEN as usize] - PIECE_VALUES[PAWN as usize];
+                piece = QUEEN;
+            }
+
+            occupancy ^= 1 << square;
+            on_target = piece;
+            side = 1 - side;
+        }
+
+        // Each side may decline to continue the exchange when recapturing doesn't pay off
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+
+        gain[0]
+    }
+
+    /// Whether the static exchange evaluation of a move is at least the threshold
+    pub fn see_ge(&self, mv: &Move, threshold: i32) -> bool {
+        self.see(mv) >= threshold
+    }
+
+    fn least_valuable_attacker(&self, attackers: u64, colour: u8) -> Option<(u8, u8)> {
+        [PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING]
+            .into_iter()
+            .find_map(|piece| {
+                let pieces = attackers & self.pieces[colour as usize][piece as usize];
+                (pieces != 0).then(|| (pieces.trailing_zeros() as u8, piece))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn see(fen: &str, mv: &str) -> i32 {
+        Board::from_fen(fen).unwrap().see(&Move::coordinate(mv))
+    }
+
+    #[test]
+    fn simple_exchanges() {
+        assert_eq!(see("4k3/8/3p4/4n3/3P4/8/8/4K3 w - - 0 1", "d4e5"), 200);
+        assert_eq!(see("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1", "e1e5"), -400);
+        assert_eq!(see("4k3/8/8/4p3/8/8/8/4RK2 w - - 0 1", "e1e5"), 100);
+        assert_eq!(see("4k3/8/8/8/8/8/8/4RK2 w - - 0 1", "e1e5"), 0);
+    }
+
+    #[test]
+    fn x_rays() {
+        assert_eq!(see("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 100);
+        assert_eq!(see("4k3/4r3/4r3/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), -400);
+        assert_eq!(see("4k3/8/2b5/3p4/8/5Q2/8/K7 w - - 0 1", "f3d5"), -800);
+        assert_eq!(see("4k3/8/2b5/3p4/4B3/5Q2/8/K7 w - - 0 1", "e4d5"), 100);
+    }
+
+    #[test]
+    fn promotions() {
+        assert_eq!(see("3rk3/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8q"), 400);
+        assert_eq!(see("4k3/8/8/8/3R4/8/2p5/3n2K1 w - - 0 1", "d4d1"), -1000);
+    }
+
+    #[test]
+    fn en_passant() {
+        assert_eq!(see("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1", "d5e6"), 100);
+        assert_eq!(see("4k3/5p2/8/3Pp3/8/8/8/4K3 w - e6 0 1", "d5e6"), 0);
+    }
+
+    #[test]
+    fn king_cannot_recapture_defended_piece() {
+        assert_eq!(see("8/8/8/3pk3/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+        assert_eq!(see("8/8/8/3pk3/8/8/3R4/4K3 w - - 0 1", "d2d5"), -400);
+        // The bishop behind the queen defends f7 once the queen has taken, so the king can't
+        assert_eq!(see("4k3/5p2/8/8/2Q5/1B6/8/4K3 w - - 0 1", "c4f7"), 100);
+        assert_eq!(see("4k3/5p2/8/8/2Q5/8/8/4K3 w - - 0 1", "c4f7"), -800);
+    }
+
+    #[test]
+    fn threshold() {
+        let board = Board::from_fen("4k3/8/3p4/4n3/3P4/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.see_ge(&Move::coordinate("d4e5"), 200));
+        assert!(!board.see_ge(&Move::coordinate("d4e5"), 201));
+    }
+}
@@ -1,10 +1,21 @@
 use std::sync::LazyLock;
 
-pub static ZOBRIST_SQUARES: LazyLock<[[[u64; 6]; 2]; 64]> =
-    LazyLock::new(rand::random::<[[[u64; 6]; 2]; 64]>);
-pub static ZOBRIST_BLACK: LazyLock<u64> = LazyLock::new(rand::random);
-pub static ZOBRIST_WHITE_KING_CASTLING: LazyLock<u64> = LazyLock::new(rand::random);
-pub static ZOBRIST_WHITE_QUEEN_CASTLING: LazyLock<u64> = LazyLock::new(rand::random);
-pub static ZOBRIST_BLACK_KING_CASTLING: LazyLock<u64> = LazyLock::new(rand::random);
-pub static ZOBRIST_BLACK_QUEEN_CASTLING: LazyLock<u64> = LazyLock::new(rand::random);
-pub static ZOBRIST_EN_PASSANT: LazyLock<[u64; 8]> = LazyLock::new(rand::random::<[u64; 8]>);
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Keys come from a fixed seed so that searches, and anything generated from them, are
+/// reproducible between runs
+fn keys<T>(seed: u64) -> T
+where
+    rand::distr::StandardUniform: rand::distr::Distribution<T>,
+{
+    StdRng::seed_from_u64(seed).random()
+}
+
+pub static ZOBRIST_SQUARES: LazyLock<[[[u64; 6]; 2]; 64]> = LazyLock::new(|| keys(0));
+pub static ZOBRIST_BLACK: LazyLock<u64> = LazyLock::new(|| keys(1));
+pub static ZOBRIST_WHITE_KING_CASTLING: LazyLock<u64> = LazyLock::new(|| keys(2));
+pub static ZOBRIST_WHITE_QUEEN_CASTLING: LazyLock<u64> = LazyLock::new(|| keys(3));
+pub static ZOBRIST_BLACK_KING_CASTLING: LazyLock<u64> = LazyLock::new(|| keys(4));
+pub static ZOBRIST_BLACK_QUEEN_CASTLING: LazyLock<u64> = LazyLock::new(|| keys(5));
+pub static ZOBRIST_EN_PASSANT: LazyLock<[u64; 8]> = LazyLock::new(|| keys(6));
@@ -0,0 +1,346 @@
+use std::iter::Peekable;
+use std::str::{FromStr, SplitWhitespace};
+
+/// A command from the GUI
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Command {
+    Uci,
+    Debug(bool),
+    IsReady,
+    /// Everything after `setoption`, as option names and values may contain spaces
+    SetOption(String),
+    Register,
+    UciNewGame,
+    Position(Position),
+    Go(Go),
+    Stop,
+    PonderHit,
+    /// Print a breakdown of the static evaluation of the current position
+    Eval,
+    Quit,
+}
+
+/// Position to search from, given by a starting point and the moves played from it
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Position {
+    pub setup: Setup,
+    pub moves: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) enum Setup {
+    #[default]
+    StartPos,
+    Fen(String),
+    /// Keep the current position and only play the moves, from `position moves ...`
+    Current,
+}
+
+/// Limits and modes of a `go` command, where times are in milliseconds
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Go {
+    pub searchmoves: Vec<String>,
+    pub ponder: bool,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub depth: Option<usize>,
+    pub nodes: Option<usize>,
+    pub mate: Option<usize>,
+    pub movetime: Option<u64>,
+    pub infinite: bool,
+    /// Print the static evaluation rather than searching
+    pub evaluate: bool,
+    /// Count the leaf nodes below each move to the given depth rather than searching, optionally
+    /// from another position
+    pub perft: Option<(usize, Option<Position>)>,
+}
+
+/// A parsed line, along with anything in it that had to be skipped
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Parsed {
+    /// The command, or `None` if the line didn't contain one
+    pub command: Option<Command>,
+    /// Tokens that weren't understood, which the protocol says to ignore
+    pub ignored: Vec<String>,
+    /// Values that were understood but invalid, and so were dropped
+    pub problems: Vec<String>,
+}
+
+/// Parse a line of input, skipping unknown tokens and carrying on with the rest of the line as
+/// the protocol asks
+pub(crate) fn parse(line: &str) -> Parsed {
+    let mut parser = Parser {
+        tokens: line.split_whitespace().peekable(),
+        parsed: Parsed::default(),
+    };
+    parser.parsed.command = parser.command();
+    parser.parsed
+}
+
+/// Whether a token is a move in coordinate notation, such as `e2e4` or `a7a8q`
+pub(crate) fn is_coordinate(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    let square =
+        |file: u8, rank: u8| (b'a'..=b'h').contains(&file) && (b'1'..=b'8').contains(&rank);
+    match bytes {
+        [a, b, c, d] => square(*a, *b) && square(*c, *d),
+        [a, b, c, d, promotion] => square(*a, *b) && square(*c, *d) && b"nbrq".contains(promotion),
+        _ => false,
+    }
+}
+
+struct Parser<'a> {
+    tokens: Peekable<SplitWhitespace<'a>>,
+    parsed: Parsed,
+}
+
+impl Parser<'_> {
+    fn command(&mut self) -> Option<Command> {
+        while let Some(token) = self.tokens.next() {
+            let command = match token {
+                "uci" => Command::Uci,
+                "debug" => self.debug(),
+                "isready" => Command::IsReady,
+                "setoption" => {
+                    Command::SetOption(self.tokens.by_ref().collect::<Vec<_>>().join(" "))
+                }
+                "register" => {
+                    // Nothing needs registering, so the name and code are dropped
+                    self.tokens.by_ref().for_each(drop);
+                    Command::Register
+                }
+                "ucinewgame" => Command::UciNewGame,
+                "position" => Command::Position(self.position()),
+                "go" => Command::Go(self.go()),
+                "stop" => Command::Stop,
+                "ponderhit" => Command::PonderHit,
+                "eval" => Command::Eval,
+                "quit" => Command::Quit,
+                _ => {
+                    self.ignore(token);
+                    continue;
+                }
+            };
+
+            self.tokens.by_ref().for_each(|token| {
+                self.parsed.ignored.push(token.to_string());
+            });
+            return Some(command);
+        }
+        None
+    }
+
+    fn ignore(&mut self, token: &str) {
+        self.parsed.ignored.push(token.to_string());
+    }
+
+    fn debug(&mut self) -> Command {
+        match self.tokens.next() {
+            Some("on") => Command::Debug(true),
+            Some("off") => Command::Debug(false),
+            value => {
+                let value = value.unwrap_or("nothing");
+                self.parsed
+                    .problems
+                    .push(format!("expected on or off for debug: {value}"));
+                Command::Debug(false)
+            }
+        }
+    }
+
+    fn position(&mut self) -> Position {
+        let mut position = Position {
+            setup: Setup::Current,
+            moves: Vec::new(),
+        };
+
+        while let Some(token) = self.tokens.next() {
+            match token {
+                "startpos" => position.setup = Setup::StartPos,
+                "fen" => {
+                    let mut fields = Vec::new();
+                    while let Some(field) = self.tokens.next_if(|token| *token != "moves") {
+                        fields.push(field);
+                    }
+                    // Some GUIs leave off the move clocks
+                    if fields.len() == 4 {
+                        fields.push("0");
+                    }
+                    if fields.len() == 5 {
+                        fields.push("1");
+                    }
+                    position.setup = Setup::Fen(fields.join(" "));
+                }
+                // Moves run to the end of the line, and are checked when they are played
+                "moves" => position
+                    .moves
+                    .extend(self.tokens.by_ref().map(String::from)),
+                _ => self.ignore(token),
+            }
+        }
+
+        position
+    }
+
+    fn go(&mut self) -> Go {
+        let mut go = Go::default();
+
+        while let Some(token) = self.tokens.next() {
+            match token {
+                "searchmoves" => {
+                    while let Some(mv) = self.tokens.next_if(|token| is_coordinate(token)) {
+                        go.searchmoves.push(mv.to_string());
+                    }
+                }
+                "ponder" => go.ponder = true,
+                "wtime" => go.wtime = self.time(token),
+                "btime" => go.btime = self.time(token),
+                "winc" => go.winc = self.time(token),
+                "binc" => go.binc = self.time(token),
+                "movestogo" => go.movestogo = self.value(token),
+                "depth" => go.depth = self.value(token),
+                "nodes" => go.nodes = self.value(token),
+                "mate" => go.mate = self.value(token),
+                "movetime" => go.movetime = self.time(token),
+                "infinite" => go.infinite = true,
+                "evaluate" => go.evaluate = true,
+                "perft" => {
+                    if let Some(depth) = self.value(token) {
+                        let position = self
+                            .tokens
+                            .peek()
+                            .is_some_and(|next| ["startpos", "fen", "moves"].contains(next))
+                            .then(|| self.position());
+                        go.perft = Some((depth, position));
+                    }
+                }
+                _ => self.ignore(token),
+            }
+        }
+
+        go
+    }
+
+    /// The value following a keyword, recording a problem if it's missing or invalid
+    fn value<T: FromStr>(&mut self, keyword: &str) -> Option<T> {
+        let value = self.tokens.next();
+        let parsed = value.and_then(|value| value.parse().ok());
+        if parsed.is_none() {
+            let value = value.unwrap_or("nothing");
+            self.parsed
+                .problems
+                .push(format!("invalid value for {keyword}: {value}"));
+        }
+        parsed
+    }
+
+    /// A time in milliseconds, where GUIs may send a negative time once a clock has run out
+    fn time(&mut self, keyword: &str) -> Option<u64> {
+        self.value::<i64>(keyword).map(|ms| ms.max(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(line: &str) -> Command {
+        parse(line).command.unwrap()
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(command("uci"), Command::Uci);
+        assert_eq!(command("  isready  "), Command::IsReady);
+        assert_eq!(command("debug on"), Command::Debug(true));
+        assert_eq!(command("register later"), Command::Register);
+        assert_eq!(
+            command("setoption name Move Overhead value 30"),
+            Command::SetOption("name Move Overhead value 30".into())
+        );
+
+        // Unknown tokens are skipped until a command is found
+        let parsed = parse("joho debug off");
+        assert_eq!(parsed.command, Some(Command::Debug(false)));
+        assert_eq!(parsed.ignored, vec!["joho".to_string()]);
+        assert_eq!(parse("hello there").command, None);
+        assert_eq!(parse("").command, None);
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(
+            command("position startpos moves e2e4 e7e5"),
+            Command::Position(Position {
+                setup: Setup::StartPos,
+                moves: vec!["e2e4".into(), "e7e5".into()],
+            })
+        );
+        assert_eq!(
+            command("position fen 8/8/4k3/8/8/4K3/4P3/8 w - - moves e2e4"),
+            Command::Position(Position {
+                setup: Setup::Fen("8/8/4k3/8/8/4K3/4P3/8 w - - 0 1".into()),
+                moves: vec!["e2e4".into()],
+            })
+        );
+        assert_eq!(
+            command("position moves a7a8q"),
+            Command::Position(Position {
+                setup: Setup::Current,
+                moves: vec!["a7a8q".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn go() {
+        assert_eq!(command("go"), Command::Go(Go::default()));
+        assert_eq!(
+            command("go wtime 300000 btime -20 winc 2000 binc 2000 movestogo 40"),
+            Command::Go(Go {
+                wtime: Some(300_000),
+                btime: Some(0),
+                winc: Some(2_000),
+                binc: Some(2_000),
+                movestogo: Some(40),
+                ..Go::default()
+            })
+        );
+        assert_eq!(
+            command("go infinite searchmoves e2e4 d2d4 ponder"),
+            Command::Go(Go {
+                searchmoves: vec!["e2e4".into(), "d2d4".into()],
+                ponder: true,
+                infinite: true,
+                ..Go::default()
+            })
+        );
+        assert_eq!(
+            command("go perft 2 startpos moves e2e4"),
+            Command::Go(Go {
+                perft: Some((
+                    2,
+                    Some(Position {
+                        setup: Setup::StartPos,
+                        moves: vec!["e2e4".into()],
+                    })
+                )),
+                ..Go::default()
+            })
+        );
+
+        let parsed = parse("go depth deep nodes 1000 sideways");
+        assert_eq!(
+            parsed.command,
+            Some(Command::Go(Go {
+                nodes: Some(1_000),
+                ..Go::default()
+            }))
+        );
+        assert_eq!(parsed.ignored, vec!["sideways".to_string()]);
+        assert_eq!(parsed.problems.len(), 1);
+    }
+}
@@ -1,75 +1,32 @@
-use clap::{Args, Subcommand};
+use chust_engine::{Board, Engine};
 
-use chust_engine::{Engine, Move};
+use crate::command::{Position, Setup};
 
-#[derive(Debug, Subcommand)]
-#[command(rename_all = "lower")]
-pub(crate) enum FenMovesCommand {
-    Fen(FenArgs),
-    StartPos(StartPosArgs),
-    Moves {
-        #[arg(required = true)]
-        moves: Vec<String>,
-    },
-}
-
-#[derive(Debug, Args)]
-pub(crate) struct FenArgs {
-    position: String,
-    colour: String,
-    castling: String,
-    en_passant: String,
-    half_clock: String,
-    full_clock: String,
-    #[command(subcommand)]
-    moves: Option<MovesCommand>,
-}
-
-#[derive(Debug, Args)]
-pub(crate) struct StartPosArgs {
-    #[command(subcommand)]
-    moves: Option<MovesCommand>,
-}
-
-#[derive(Debug, Subcommand)]
-pub(crate) enum MovesCommand {
-    Moves { moves: Vec<String> },
-}
-
-pub(crate) fn apply_fen_and_moves(cmd: FenMovesCommand, engine: &mut Engine) {
-    match cmd {
-        FenMovesCommand::Fen(cmd) => {
-            let fen = format!(
-                "{} {} {} {} {} {}",
-                cmd.position,
-                cmd.colour,
-                cmd.castling,
-                cmd.en_passant,
-                cmd.half_clock,
-                cmd.full_clock
-            );
+/// Set up the position, stopping at the first move that isn't legal
+pub(crate) fn apply_position(position: Position, engine: &mut Engine) -> Result<(), String> {
+    match position.setup {
+        Setup::StartPos => engine.reset(None),
+        Setup::Fen(fen) => {
+            Board::from_fen(&fen).map_err(|_| format!("invalid fen: {fen}"))?;
             engine.reset(Some(fen));
-
-            if let Some(moves) = cmd.moves {
-                let MovesCommand::Moves { moves } = moves;
-                apply_moves(moves, engine);
-            }
-        }
-        FenMovesCommand::StartPos(cmd) => {
-            engine.reset(None);
-            if let Some(moves) = cmd.moves {
-                let MovesCommand::Moves { moves } = moves;
-                apply_moves(moves, engine);
-            }
-        }
-        FenMovesCommand::Moves { moves } => {
-            apply_moves(moves, engine);
         }
+        Setup::Current => {}
     }
+
+    apply_moves(&position.moves, engine)
 }
 
-pub(crate) fn apply_moves(moves: Vec<String>, engine: &mut Engine) {
-    for mv in &moves {
-        engine.board.make_move(&Move::coordinate(mv));
+/// Play moves in coordinate notation, matching them against the legal moves so that a bad move
+/// from the GUI can't corrupt the board
+pub(crate) fn apply_moves(moves: &[String], engine: &mut Engine) -> Result<(), String> {
+    for mv in moves {
+        let legal = engine
+            .board
+            .moves()
+            .into_iter()
+            .find(|legal| legal.to_string() == *mv)
+            .ok_or_else(|| format!("illegal move: {mv}"))?;
+        engine.board.make_move(&legal);
     }
+    Ok(())
 }
@@ -1,33 +1,84 @@
-use clap::Subcommand;
+use std::time::Duration;
 
-use chust_engine::Engine;
+use chust_engine::{Engine, SearchLimits, SearchThread, WHITE, allocate_time};
 
-use crate::fen_move::*;
+use crate::command::Go;
+use crate::fen_move::apply_position;
 
-#[derive(Debug, Subcommand)]
-#[command(rename_all = "lower")]
-pub enum GoCommands {
-    Depth {
-        depth: usize,
-    },
-    Evaluate,
-    Perft {
-        depth: usize,
-        #[command(subcommand)]
-        fen: Option<FenMovesCommand>,
-    },
-}
-
-pub fn invoke_go(engine: &mut Engine, go_cmd: GoCommands) {
-    match go_cmd {
-        GoCommands::Depth { depth } => engine.search_depth(depth),
-        GoCommands::Evaluate => println!("{}", engine.evaluate()),
-        GoCommands::Perft { depth, fen } => {
-            if let Some(fen) = fen {
-                apply_fen_and_moves(fen, engine);
-            }
+/// Plies searched beyond the length of the mate asked for by `go mate`
+const MATE_SLACK: usize = 2;
 
-            engine.board.divide(depth);
+/// Start the search asked for by `go` on the search thread, or run the engine's own `go eval`
+/// and `go perft` extensions straight away
+pub(crate) fn invoke_go(thread: &mut SearchThread, go: Go) -> Result<(), String> {
+    let engine = thread.engine();
+    if go.evaluate {
+        println!("{}", engine.evaluate());
+    } else if let Some((depth, position)) = go.perft {
+        if depth == 0 {
+            return Err("perft depth must be at least 1".to_string());
         }
+        if let Some(position) = position {
+            apply_position(position, engine)?;
+        }
+        engine.board.divide(depth);
+    } else {
+        let ponder = go.ponder;
+        let limits = search_limits(engine, go)?;
+        // The search reports its own move when it finishes
+        thread.start(limits, ponder, |_, _| {});
     }
+    Ok(())
+}
+
+/// Turn the limits given to `go` into the engine's, allotting time from the clock when no move
+/// time is given
+fn search_limits(engine: &mut Engine, go: Go) -> Result<SearchLimits, String> {
+    let legal = engine.board.moves();
+    let searchmoves = go
+        .searchmoves
+        .iter()
+        .map(|mv| {
+            legal
+                .iter()
+                .find(|legal| legal.to_string() == *mv)
+                .copied()
+                .ok_or_else(|| format!("illegal move in searchmoves: {mv}"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let overhead = Duration::from_millis(engine.options.move_overhead);
+    let (time, increment) = if engine.board.active_colour == WHITE {
+        (go.wtime, go.winc)
+    } else {
+        (go.btime, go.binc)
+    };
+    let movetime = match (go.movetime, time) {
+        (Some(movetime), _) => Some(Duration::from_millis(movetime).saturating_sub(overhead)),
+        (None, Some(time)) => Some(allocate_time(
+            Duration::from_millis(time),
+            Duration::from_millis(increment.unwrap_or(0)),
+            go.movestogo
+                .map(|moves| u32::try_from(moves).unwrap_or(u32::MAX)),
+            overhead,
+        )),
+        (None, None) => None,
+    };
+
+    // A mate in n moves is found within n * 2 - 1 plies, with some slack for pruned lines, so a
+    // mate search still finishes when there's no mate to find. Otherwise a search without limits
+    // runs until `stop`
+    let unlimited = go.depth.is_none() && go.nodes.is_none() && movetime.is_none();
+    let mate_depth = go
+        .mate
+        .filter(|_| unlimited)
+        .map(|mate| mate * 2 + MATE_SLACK);
+    Ok(SearchLimits {
+        depth: go.depth.or(mate_depth),
+        nodes: go.nodes,
+        movetime,
+        mate: go.mate,
+        searchmoves,
+        infinite: go.infinite,
+    })
 }
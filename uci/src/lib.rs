@@ -1,53 +1,134 @@
-use clap::{Parser, Subcommand};
+use chust_engine::{Engine, SearchThread, registry};
 
-use chust_engine::Engine;
-
-use crate::fen_move::{FenMovesCommand, apply_fen_and_moves};
-use crate::go::{GoCommands, invoke_go};
+use crate::command::{Command, parse};
+use crate::fen_move::apply_position;
+use crate::go::invoke_go;
+use crate::options::{advertise, set_option};
 
+mod command;
 mod fen_move;
 mod go;
+mod options;
 
 const NAME: &str = "Chust";
 const AUTHOR: &str = "Joel Milligan";
 
-#[derive(Debug, Parser)]
-#[command(multicall = true)]
-struct Cli {
-    #[command(subcommand)]
-    cmd: Commands,
+/// An engine driven over UCI, searching on its own thread so that `stop`, `ponderhit` and
+/// `isready` are answered while it thinks
+pub struct Uci {
+    thread: SearchThread,
+    /// Whether to send the GUI extra information, set by `debug on`
+    debug: bool,
 }
 
-#[derive(Debug, Subcommand)]
-#[command(rename_all = "lower")]
-enum Commands {
-    Uci,
-    IsReady,
-    UciNewGame,
-    Position {
-        #[command(subcommand)]
-        cmd: FenMovesCommand,
-    },
-    Go {
-        #[command(subcommand)]
-        cmd: GoCommands,
-    },
-    Quit,
+impl Default for Uci {
+    fn default() -> Self {
+        Self::new(Engine::new())
+    }
 }
 
-pub fn respond(line: &str, engine: &mut Engine) -> Result<bool, String> {
-    let args = line.split_whitespace();
-    let cli = Cli::try_parse_from(args).map_err(|e| e.to_string())?;
-    match cli.cmd {
-        Commands::Uci => println!("id name {NAME}\nid author {AUTHOR}\nuciok"),
-        Commands::UciNewGame => {
-            engine.reset(None);
-            println!("readyok");
+impl Uci {
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            thread: SearchThread::new(engine),
+            debug: false,
+        }
+    }
+
+    /// The engine, once any search running has been stopped
+    pub fn engine(&mut self) -> &mut Engine {
+        self.thread.stop();
+        self.thread.engine()
+    }
+
+    /// Respond to a line from the GUI, returning whether to quit
+    ///
+    /// Problems with the line are reported as `info string`, and in debug mode so are any tokens
+    /// that were ignored. Errors are for commands that were understood but couldn't be carried
+    /// out.
+    pub fn respond(&mut self, line: &str) -> Result<bool, String> {
+        let parsed = parse(line);
+        for problem in &parsed.problems {
+            println!("info string {problem}");
+        }
+        if self.debug && !parsed.ignored.is_empty() {
+            println!("info string ignored {}", parsed.ignored.join(" "));
+        }
+
+        let Some(command) = parsed.command else {
+            return Ok(false);
+        };
+        match command {
+            Command::Uci => {
+                println!("id name {NAME}\nid author {AUTHOR}");
+                for option in registry() {
+                    println!("{}", advertise(&option));
+                }
+                println!("uciok");
+            }
+            Command::Debug(debug) => self.debug = debug,
+            // Commands that change the engine's state end any search the GUI didn't stop
+            Command::UciNewGame => {
+                let engine = self.engine();
+                engine.reset(None);
+                engine.clear_hash();
+            }
+            Command::SetOption(args) => set_option(&args, self.engine())?,
+            Command::IsReady => println!("readyok"),
+            Command::Position(position) => apply_position(position, self.engine())?,
+            Command::Go(go) => {
+                self.thread.stop();
+                invoke_go(&mut self.thread, go)?;
+            }
+            Command::Stop => self.thread.stop(),
+            Command::PonderHit => self.thread.ponderhit(),
+            Command::Register => {}
+            Command::Eval => println!("{}", self.engine().trace()),
+            Command::Quit => {
+                self.thread.stop();
+                return Ok(true);
+            }
         }
-        Commands::IsReady => println!("readyok"),
-        Commands::Position { cmd } => apply_fen_and_moves(cmd, engine),
-        Commands::Go { cmd } => invoke_go(engine, cmd),
-        Commands::Quit => return Ok(true),
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn infinite() {
+        let mut uci = Uci::default();
+        uci.respond("position startpos moves e2e4").unwrap();
+        uci.respond("go infinite").unwrap();
+        sleep(Duration::from_millis(200));
+        assert!(uci.thread.searching());
+
+        // The GUI is still answered while the search runs
+        uci.respond("isready").unwrap();
+        assert!(uci.thread.searching());
+
+        uci.respond("stop").unwrap();
+        assert!(!uci.thread.searching());
+        assert!(uci.engine().root_depth > 1);
+    }
+
+    #[test]
+    fn ponder() {
+        let mut uci = Uci::default();
+        uci.respond("setoption name Ponder value true").unwrap();
+
+        // A clock this short would stop a normal search straight away
+        uci.respond("go ponder wtime 100 btime 100").unwrap();
+        sleep(Duration::from_millis(300));
+        assert!(uci.thread.searching());
+
+        // Once the expected move is played the time is up, so the search ends by itself
+        uci.respond("ponderhit").unwrap();
+        uci.thread.wait();
+        assert!(!uci.thread.searching());
     }
-    Ok(false)
 }
@@ -1,26 +1,26 @@
-use chust_engine::Engine;
-use chust_uci::respond;
+use chust_uci::Uci;
 
 fn main() -> Result<(), String> {
-    let mut engine = Engine::new();
+    let mut uci = Uci::default();
 
     loop {
         let mut buffer = String::new();
-        std::io::stdin()
+        let read = std::io::stdin()
             .read_line(&mut buffer)
             .map_err(|e| e.to_string())?;
-        let line = buffer.trim();
+        // The GUI has closed its end of the pipe, which is as good as `quit`
+        let line = if read == 0 { "quit" } else { buffer.trim() };
         if line.is_empty() {
             continue;
         }
 
-        match respond(line, &mut engine) {
+        match uci.respond(line) {
             Ok(quit) => {
                 if quit {
                     break;
                 }
             }
-            Err(e) => eprintln!("{e}"),
+            Err(e) => println!("info string {e}"),
         }
     }
 
@@ -0,0 +1,85 @@
+use chust_engine::{Engine, GuiOption, OptionType, registry};
+
+/// `option` line advertising an option to the GUI in response to `uci`
+pub(crate) fn advertise(option: &GuiOption) -> String {
+    let option_type = match &option.option_type {
+        OptionType::Check { default } => format!("check default {default}"),
+        OptionType::Spin { default, min, max } => {
+            format!("spin default {default} min {min} max {max}")
+        }
+        OptionType::Combo { default, vars } => {
+            let vars: String = vars.iter().map(|var| format!(" var {var}")).collect();
+            format!("combo default {default}{vars}")
+        }
+        OptionType::Button => "button".to_string(),
+        OptionType::String { default: "" } => "string default <empty>".to_string(),
+        OptionType::String { default } => format!("string default {default}"),
+    };
+    format!("option name {} type {option_type}", option.name)
+}
+
+/// Apply `setoption name <name> [value <value>]`, where both name and value may contain spaces
+pub(crate) fn set_option(args: &str, engine: &mut Engine) -> Result<(), String> {
+    let args = args
+        .strip_prefix("name ")
+        .ok_or_else(|| format!("expected option name: {args}"))?;
+    let (name, value) = match args.split_once(" value ") {
+        Some((name, value)) => (name.trim(), Some(value.trim())),
+        None => (args.trim(), None),
+    };
+
+    registry()
+        .iter()
+        .find(|option| option.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("unknown option: {name}"))?
+        .set(engine, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chust_engine::OptionValue;
+
+    #[test]
+    fn advertised() {
+        let options: Vec<String> = registry().iter().map(advertise).collect();
+        assert!(options.contains(&"option name Hash type spin default 16 min 1 max 1024".into()));
+        assert!(options.contains(&"option name Clear Hash type button".into()));
+        assert!(options.contains(&"option name EvalFile type string default <empty>".into()));
+        assert!(options.contains(&"option name Threads type spin default 1 min 1 max 1".into()));
+
+        let combo = GuiOption::combo("Style", "Normal", &["Solid", "Normal"], |_, _| {});
+        assert_eq!(
+            advertise(&combo),
+            "option name Style type combo default Normal var Solid var Normal"
+        );
+    }
+
+    #[test]
+    fn parsing() {
+        let mut engine = Engine::new();
+        set_option("name Move Overhead value 250", &mut engine).unwrap();
+        assert_eq!(engine.options.move_overhead, 250);
+        assert!(set_option("name evalfile value /nonexistent/net.bin", &mut engine).is_err());
+        set_option("name EvalFile value <empty>", &mut engine).unwrap();
+        assert_eq!(engine.network, None);
+        set_option("name Ponder value true", &mut engine).unwrap();
+        assert!(engine.options.ponder);
+        set_option("name Clear Hash", &mut engine).unwrap();
+
+        assert!(set_option("name Threads value 0", &mut engine).is_err());
+        assert!(set_option("name Threads value 2", &mut engine).is_err());
+        assert!(set_option("name MultiPV value many", &mut engine).is_err());
+        assert!(set_option("name Ponder value yes", &mut engine).is_err());
+        assert!(set_option("name Hash", &mut engine).is_err());
+        assert!(set_option("name Unknown value 1", &mut engine).is_err());
+        assert_eq!(engine.options.threads, 1);
+
+        let combo = GuiOption::combo("Style", "Normal", &["Solid", "Normal"], |_, _| {});
+        assert_eq!(
+            combo.parse(Some("solid")),
+            Ok(OptionValue::Combo("Solid".into()))
+        );
+        assert!(combo.parse(Some("Wild")).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use chust_engine::allocate_time;
+
+/// Time control from `level` or `st`, along with the engine's clock from `time`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Clock {
+    /// Moves in each time control, or 0 when the base time is for the whole game
+    pub moves_per_session: u32,
+    pub base: Duration,
+    pub increment: Duration,
+    /// Exact time for every move, from `st`
+    pub fixed: Option<Duration>,
+    /// Time left on the engine's clock, as last reported by the GUI
+    pub time: Option<Duration>,
+}
+
+impl Clock {
+    /// Apply `level MPS BASE INC`, where the base is in minutes, optionally with seconds after a
+    /// colon, and the increment is in seconds
+    pub fn set_level(&mut self, args: &[&str]) -> Result<(), String> {
+        let [moves, base, increment] = args else {
+            return Err(format!(
+                "expected moves, base and increment: {}",
+                args.join(" ")
+            ));
+        };
+
+        let base = match base.split_once(':') {
+            Some((minutes, seconds)) => number(minutes)? * 60.0 + number(seconds)?,
+            None => number(base)? * 60.0,
+        };
+
+        *self = Clock {
+            moves_per_session: moves
+                .parse()
+                .map_err(|_| format!("invalid moves: {moves}"))?,
+            base: Duration::from_secs_f64(base),
+            increment: Duration::from_secs_f64(number(increment)?),
+            fixed: None,
+            time: None,
+        };
+        Ok(())
+    }
+
+    /// Apply `st SECONDS`
+    pub fn set_fixed(&mut self, seconds: &str) -> Result<(), String> {
+        self.fixed = Some(Duration::from_secs_f64(number(seconds)?));
+        Ok(())
+    }
+
+    /// Apply `time CENTISECONDS`
+    pub fn set_time(&mut self, centiseconds: &str) -> Result<(), String> {
+        let centiseconds: i64 = centiseconds
+            .parse()
+            .map_err(|_| format!("invalid time: {centiseconds}"))?;
+        // The clock may have run out by the time the GUI reports it
+        self.time = Some(Duration::from_millis(centiseconds.max(0) as u64 * 10));
+        Ok(())
+    }
+
+    /// Time to spend on the next move after the engine has made the given number of moves, or
+    /// `None` when no time control has been set
+    pub fn movetime(&self, moves_made: usize, overhead: Duration) -> Option<Duration> {
+        if let Some(fixed) = self.fixed {
+            return Some(fixed.saturating_sub(overhead));
+        }
+
+        let time = self.time.or((!self.base.is_zero()).then_some(self.base))?;
+        let moves_to_go = (self.moves_per_session > 0)
+            .then(|| self.moves_per_session - moves_made as u32 % self.moves_per_session);
+        Some(allocate_time(time, self.increment, moves_to_go, overhead))
+    }
+}
+
+fn number(value: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite() && *value >= 0.0)
+        .ok_or_else(|| format!("invalid number: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_controls() {
+        let secs = Duration::from_secs;
+        let mut clock = Clock::default();
+        assert_eq!(clock.movetime(0, Duration::ZERO), None);
+
+        clock.set_level(&["40", "5", "0"]).unwrap();
+        assert_eq!(clock.base, secs(300));
+        assert_eq!(clock.movetime(0, Duration::ZERO), Some(secs(300) / 40));
+        // The last move before the time control may use everything that's left
+        clock.set_time("1000").unwrap();
+        assert_eq!(clock.movetime(39, Duration::ZERO), Some(secs(10)));
+
+        clock.set_level(&["0", "0:30", "0.5"]).unwrap();
+        assert_eq!(clock.base, secs(30));
+        assert_eq!(clock.increment, Duration::from_millis(500));
+        assert_eq!(clock.time, None);
+
+        clock.set_fixed("2").unwrap();
+        assert_eq!(
+            clock.movetime(10, Duration::from_millis(100)),
+            Some(Duration::from_millis(1_900))
+        );
+
+        assert!(clock.set_level(&["40", "5"]).is_err());
+        assert!(clock.set_level(&["40", "five", "0"]).is_err());
+        assert!(clock.set_time("soon").is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chust_engine::{BLACK, Board, Engine, Move, Protocol, SearchLimits, SearchThread, WHITE};
+
+use crate::clock::Clock;
+use crate::options::{features, set_option};
+
+mod clock;
+mod options;
+
+const NAME: &str = "Chust";
+
+/// A game driven over CECP, tracking what the protocol needs on top of the engine
+pub struct XBoard {
+    /// The engine, searching on its own thread so that `?`, `exit` and moves are read while it
+    /// thinks or analyses
+    thread: SearchThread,
+    /// Side the engine plays, or `None` in force mode where moves are only recorded
+    engine_colour: Option<u8>,
+    /// Whether each position is searched and its thinking shown without playing a move
+    analyzing: bool,
+    /// Whether thinking output is shown, set by `post`
+    post: bool,
+    /// Depth limit from `sd`
+    depth: Option<usize>,
+    clock: Clock,
+    /// Set when the search is interrupted by a command that makes its move unwanted, rather
+    /// than by `?` asking for it straight away
+    discard: Arc<AtomicBool>,
+}
+
+impl Default for XBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XBoard {
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.options.protocol = Protocol::Xboard;
+
+        Self {
+            thread: SearchThread::new(engine),
+            engine_colour: Some(BLACK),
+            analyzing: false,
+            post: false,
+            depth: None,
+            clock: Clock::default(),
+            discard: Arc::default(),
+        }
+    }
+
+    /// The engine, once any search running has been stopped without playing its move
+    pub fn engine(&mut self) -> &mut Engine {
+        if self.thread.searching() {
+            self.discard.store(true, Ordering::Relaxed);
+            self.thread.stop();
+        }
+        self.thread.engine()
+    }
+
+    /// Respond to a line from the GUI, returning whether to quit
+    ///
+    /// Errors are returned in the form the protocol expects them to be sent back in.
+    pub fn respond(&mut self, line: &str) -> Result<bool, String> {
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        let invalid = |e: String| format!("Error ({e}): {line}");
+
+        match command {
+            "protover" => {
+                println!("feature done=0");
+                println!(
+                    "feature myname=\"{NAME}\" ping=1 setboard=1 usermove=1 playother=1 analyze=1 \
+                     colors=0 time=1 draw=0 sigint=0 sigterm=0 reuse=1"
+                );
+                for feature in features() {
+                    println!("feature option=\"{feature}\"");
+                }
+                println!("feature done=1");
+            }
+            "new" => {
+                let engine = self.engine();
+                engine.reset(None);
+                engine.clear_hash();
+                self.engine_colour = Some(BLACK);
+                self.depth = None;
+                self.clock = Clock::default();
+            }
+            "setboard" => {
+                Board::from_fen(args).map_err(|_| invalid("invalid position".into()))?;
+                self.engine().reset(Some(args.to_string()));
+                self.position_changed();
+            }
+            "usermove" => self.user_move(args)?,
+            "go" => {
+                self.engine_colour = Some(self.engine().board.active_colour);
+                self.think();
+            }
+            "playother" => self.engine_colour = Some(1 - self.engine().board.active_colour),
+            "force" | "result" => {
+                self.engine();
+                self.engine_colour = None;
+            }
+            "undo" => self.take_back(1).map_err(invalid)?,
+            "remove" => self.take_back(2).map_err(invalid)?,
+            "level" => {
+                let args: Vec<&str> = args.split_whitespace().collect();
+                self.clock.set_level(&args).map_err(invalid)?;
+            }
+            "st" => self.clock.set_fixed(args).map_err(invalid)?,
+            "sd" => {
+                let depth = args.parse().map_err(|_| invalid("invalid depth".into()))?;
+                self.depth = Some(depth);
+            }
+            "time" => self.clock.set_time(args).map_err(invalid)?,
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "analyze" => {
+                self.analyzing = true;
+                self.engine_colour = None;
+                self.analyze();
+            }
+            "exit" => {
+                self.engine();
+                self.analyzing = false;
+            }
+            // Move now, playing the best move found so far
+            "?" => self.thread.stop(),
+            "ping" => println!("pong {args}"),
+            "memory" => {
+                let megabytes = args.parse().map_err(|_| invalid("invalid memory".into()))?;
+                self.engine().set_hash_size(megabytes);
+            }
+            "option" => set_option(args, self.engine()).map_err(invalid)?,
+            "quit" => {
+                self.engine();
+                return Ok(true);
+            }
+            // Information the engine has no use for, including the periodic `.` status request
+            // in analyze mode, as the thinking output already covers it
+            "xboard" | "accepted" | "rejected" | "otim" | "hard" | "easy" | "random"
+            | "computer" | "name" | "rating" | "ics" | "." => {}
+            // Moves are sent bare if the GUI didn't accept `usermove`
+            _ if self.legal_move(command).is_some() => self.user_move(command)?,
+            _ => return Err(format!("Error (unknown command): {command}")),
+        }
+
+        Ok(false)
+    }
+
+    fn legal_move(&mut self, mv: &str) -> Option<Move> {
+        self.engine()
+            .board
+            .moves()
+            .into_iter()
+            .find(|legal| legal.to_string() == mv)
+    }
+
+    /// Play the opponent's move, then reply if it's the engine's turn
+    fn user_move(&mut self, mv: &str) -> Result<(), String> {
+        let mv = self
+            .legal_move(mv)
+            .ok_or_else(|| format!("Illegal move: {mv}"))?;
+        let board = &mut self.engine().board;
+        board.make_move(&mv);
+        let active_colour = board.active_colour;
+
+        if self.engine_colour == Some(active_colour) {
+            self.think();
+        } else {
+            self.position_changed();
+        }
+        Ok(())
+    }
+
+    fn take_back(&mut self, moves: usize) -> Result<(), String> {
+        let board = &mut self.engine().board;
+        if board.moves_played() < moves {
+            return Err("no moves to take back".into());
+        }
+
+        for _ in 0..moves {
+            board.unmake_move();
+        }
+        self.position_changed();
+        Ok(())
+    }
+
+    fn position_changed(&mut self) {
+        if self.analyzing {
+            self.analyze();
+        }
+    }
+
+    /// Search the position on the search thread, which plays the best move and announces the
+    /// result if the game is over
+    fn think(&mut self) {
+        let post = self.post;
+        let engine = self.engine();
+        if announce_result(&mut engine.board) {
+            return;
+        }
+        engine.silent = !post;
+
+        let overhead = Duration::from_millis(engine.options.move_overhead);
+        let moves_made = engine.board.moves_played() / 2;
+        let movetime = self.clock.movetime(moves_made, overhead);
+
+        self.discard.store(false, Ordering::Relaxed);
+        let discard = self.discard.clone();
+        let limits = SearchLimits {
+            depth: self.depth,
+            movetime,
+            ..SearchLimits::default()
+        };
+        self.thread.start(limits, false, move |engine, best_move| {
+            if let Some(mv) = best_move
+                && !discard.load(Ordering::Relaxed)
+            {
+                engine.board.make_move(&mv);
+                println!("move {mv}");
+                announce_result(&mut engine.board);
+            }
+        });
+    }
+
+    /// Search the position until the next command, showing the thinking without playing a move
+    fn analyze(&mut self) {
+        let depth = self.depth;
+        self.engine().silent = false;
+        let limits = SearchLimits {
+            depth,
+            infinite: true,
+            ..SearchLimits::default()
+        };
+        self.thread.start(limits, false, |_, _| {});
+    }
+}
+
+/// Send the result if the game has ended, returning whether it has
+fn announce_result(board: &mut Board) -> bool {
+    let result = if !board.moves().is_empty() {
+        (board.half_moves >= 100).then_some("1/2-1/2 {Fifty move rule}")
+    } else if !board.in_check() {
+        Some("1/2-1/2 {Stalemate}")
+    } else if board.active_colour == WHITE {
+        Some("0-1 {Black mates}")
+    } else {
+        Some("1-0 {White mates}")
+    };
+
+    if let Some(result) = result {
+        println!("{result}");
+    }
+    result.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    const FOOLS_MATE: &str = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2";
+
+    #[test]
+    fn playing() {
+        let mut xboard = XBoard::new();
+        for line in ["xboard", "protover 2", "new", "force", "sd 2"] {
+            assert_eq!(xboard.respond(line), Ok(false));
+        }
+
+        // Moves are recorded without a reply in force mode
+        xboard.respond("usermove e2e4").unwrap();
+        xboard.respond("e7e5").unwrap();
+        assert_eq!(xboard.engine().board.moves_played(), 2);
+        assert!(xboard.respond("usermove e1e3").is_err());
+        assert!(xboard.respond("frobnicate").is_err());
+
+        // Asked to move, the engine plays White and replies to Black's moves
+        xboard.respond("go").unwrap();
+        xboard.thread.wait();
+        assert_eq!(xboard.engine_colour, Some(WHITE));
+        assert_eq!(xboard.engine().board.moves_played(), 3);
+        let reply = xboard.engine().board.moves()[0];
+        xboard.respond(&format!("usermove {reply}")).unwrap();
+        xboard.thread.wait();
+        assert_eq!(xboard.engine().board.moves_played(), 5);
+        assert_eq!(xboard.engine().board.active_colour, BLACK);
+
+        xboard.respond("remove").unwrap();
+        assert_eq!(xboard.engine().board.moves_played(), 3);
+        xboard.respond("undo").unwrap();
+        xboard.respond("undo").unwrap();
+        xboard.respond("undo").unwrap();
+        assert!(xboard.respond("undo").is_err());
+        assert_eq!(xboard.engine().board.fen(), Board::default().fen());
+
+        assert_eq!(xboard.respond("quit"), Ok(true));
+    }
+
+    #[test]
+    fn interrupting() {
+        let mut xboard = XBoard::new();
+        xboard.respond("new").unwrap();
+
+        // Without a time control or depth the engine thinks until told to move now
+        xboard.respond("go").unwrap();
+        sleep(Duration::from_millis(200));
+        assert!(xboard.thread.searching());
+        xboard.respond("?").unwrap();
+        assert!(!xboard.thread.searching());
+        assert_eq!(xboard.engine().board.moves_played(), 1);
+
+        // Analysis carries on until the position changes or it is left
+        xboard.respond("analyze").unwrap();
+        sleep(Duration::from_millis(200));
+        assert!(xboard.thread.searching());
+        xboard.respond(".").unwrap();
+        xboard.respond("e7e5").unwrap();
+        sleep(Duration::from_millis(200));
+        assert!(xboard.thread.searching());
+        xboard.respond("exit").unwrap();
+        assert!(!xboard.thread.searching());
+        assert_eq!(xboard.engine().board.moves_played(), 2);
+
+        // A search cut short by a new game doesn't play its move
+        xboard.respond("go").unwrap();
+        xboard.respond("new").unwrap();
+        assert_eq!(xboard.engine().board.moves_played(), 0);
+    }
+
+    #[test]
+    fn positions() {
+        let mut xboard = XBoard::new();
+        xboard.respond("new").unwrap();
+        assert!(xboard.respond("setboard 8/8/8/8 w").is_err());
+
+        xboard.respond(&format!("setboard {FOOLS_MATE}")).unwrap();
+        assert_eq!(xboard.engine().board.fen(), FOOLS_MATE);
+        assert!(xboard.respond("undo").is_err());
+
+        // Black mates straight away
+        xboard.respond("sd 3").unwrap();
+        xboard.respond("go").unwrap();
+        xboard.thread.wait();
+        assert!(xboard.engine().board.moves().is_empty());
+        assert!(xboard.engine().board.in_check());
+
+        xboard.respond("level 40 5 0").unwrap();
+        assert_eq!(xboard.clock.base, Duration::from_secs(300));
+        assert!(xboard.respond("level 40 5").is_err());
+        xboard.respond("option Move Overhead=25").unwrap();
+        assert_eq!(xboard.engine().options.move_overhead, 25);
+    }
+}
@@ -0,0 +1,28 @@
+use chust_xboard::XBoard;
+
+fn main() -> Result<(), String> {
+    let mut xboard = XBoard::new();
+
+    loop {
+        let mut buffer = String::new();
+        let read = std::io::stdin()
+            .read_line(&mut buffer)
+            .map_err(|e| e.to_string())?;
+        // The GUI has closed its end of the pipe, which is as good as `quit`
+        let line = if read == 0 { "quit" } else { buffer.trim() };
+        if line.is_empty() {
+            continue;
+        }
+
+        match xboard.respond(line) {
+            Ok(quit) => {
+                if quit {
+                    break;
+                }
+            }
+            Err(e) => println!("{e}"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use chust_engine::{Engine, GuiOption, OptionType, registry};
+
+/// Options that only mean something to a UCI GUI
+fn uci_only(option: &GuiOption) -> bool {
+    option.name.starts_with("UCI_") || option.name == "Ponder"
+}
+
+/// `feature option` values advertising the engine's options
+pub(crate) fn features() -> Vec<String> {
+    registry()
+        .iter()
+        .filter(|option| !uci_only(option))
+        .map(|option| {
+            let name = option.name;
+            match &option.option_type {
+                OptionType::Check { default } => format!("{name} -check {}", *default as u8),
+                OptionType::Spin { default, min, max } => {
+                    format!("{name} -spin {default} {min} {max}")
+                }
+                OptionType::Combo { default, vars } => {
+                    let choices: Vec<String> = vars
+                        .iter()
+                        .map(|var| {
+                            if var == default {
+                                format!("*{var}")
+                            } else {
+                                var.to_string()
+                            }
+                        })
+                        .collect();
+                    format!("{name} -combo {}", choices.join(" /// "))
+                }
+                OptionType::Button => format!("{name} -button"),
+                OptionType::String { default } => format!("{name} -string {default}"),
+            }
+        })
+        .collect()
+}
+
+/// Apply `option NAME=VALUE`, or `option NAME` for a button
+pub(crate) fn set_option(args: &str, engine: &mut Engine) -> Result<(), String> {
+    let (name, value) = match args.split_once('=') {
+        Some((name, value)) => (name.trim(), Some(value.trim())),
+        None => (args.trim(), None),
+    };
+
+    let option = registry()
+        .into_iter()
+        .find(|option| !uci_only(option) && option.name == name)
+        .ok_or_else(|| format!("unknown option: {name}"))?;
+
+    // Check options are sent as 1 or 0 rather than true or false
+    let value = match (&option.option_type, value) {
+        (OptionType::Check { .. }, Some("1")) => Some("true"),
+        (OptionType::Check { .. }, Some("0")) => Some("false"),
+        (OptionType::String { .. }, Some("")) => Some("<empty>"),
+        (_, value) => value,
+    };
+    option.set(engine, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options() {
+        let features = features();
+        assert!(features.contains(&"Hash -spin 16 1 1024".to_string()));
+        assert!(features.contains(&"UseNNUE -check 0".to_string()));
+        assert!(features.contains(&"Clear Hash -button".to_string()));
+        assert!(!features.iter().any(|feature| feature.starts_with("UCI_")));
+
+        let mut engine = Engine::new();
+        set_option("Move Overhead=40", &mut engine).unwrap();
+        assert_eq!(engine.options.move_overhead, 40);
+        set_option("UseNNUE=1", &mut engine).unwrap();
+        assert!(engine.use_nnue);
+        set_option("EvalFile=", &mut engine).unwrap();
+        assert_eq!(engine.network, None);
+        set_option("Clear Hash", &mut engine).unwrap();
+
+        assert!(set_option("Threads=0", &mut engine).is_err());
+        assert!(set_option("UCI_ShowWDL=1", &mut engine).is_err());
+    }
+}